@@ -0,0 +1,84 @@
+use advent_of_code_2024::day17::{self, Breakpoint, Debugger};
+use std::{env, fs, process};
+
+const USAGE: &str = "Usage: day17 <disasm|pseudo|asm|trace> [input file] [options]
+
+The input file defaults to input/2024/day17.txt.
+trace options:
+  --a <value>          start with this value in register A
+  --break-pc <pc>      stop before the instruction at pc
+  --break-out <count>  stop after count outputs
+  --max-steps <count>  stop after count steps, 100000 by default
+  --json               print the trace as JSON instead of a table";
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2)
+}
+
+fn read_input(path: Option<&String>) -> String {
+    let path = path.map_or("input/2024/day17.txt", String::as_str);
+    fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Can't read {path}: {e}");
+        process::exit(1)
+    })
+}
+
+fn number<T: std::str::FromStr>(value: Option<&String>) -> T {
+    value
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| usage())
+}
+
+fn trace(input: &str, options: &[String]) -> Result<String, String> {
+    let mut debugger = Debugger::new(input).map_err(|e| format!("Bad input at {e}"))?;
+    let mut max_steps = 100_000;
+    let mut json = false;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--a" => debugger.set_a(number(options.next())),
+            "--break-pc" => debugger.add_breakpoint(Breakpoint::Pc(number(options.next()))),
+            "--break-out" => {
+                debugger.add_breakpoint(Breakpoint::OutputCount(number(options.next())))
+            }
+            "--max-steps" => max_steps = number(options.next()),
+            "--json" => json = true,
+            _ => usage(),
+        }
+    }
+    let stop = debugger.run(max_steps);
+    eprintln!("Stopped: {stop:?}");
+    Ok(if json {
+        debugger.to_json() + "\n"
+    } else {
+        debugger.table()
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(command) = args.first() else { usage() };
+    let file = args.get(1).filter(|a| !a.starts_with("--"));
+    let options = &args[1 + file.is_some() as usize..];
+    let input = read_input(file);
+    let bad_input = |e| format!("Bad input at {e}");
+    let result = match command.as_str() {
+        "disasm" => day17::disassemble(&input)
+            .map(|d| d.listing())
+            .map_err(bad_input),
+        "pseudo" => day17::disassemble(&input)
+            .map(|d| d.pseudocode())
+            .map_err(bad_input),
+        "asm" => day17::assemble(&input).map_err(bad_input),
+        "trace" => trace(&input, options),
+        _ => usage(),
+    };
+    match result {
+        Ok(output) => print!("{output}"),
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1)
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use advent_of_code_2024::day24::{self, Format};
+use std::{env, fs, process};
+
+const USAGE: &str = "Usage: day24 <dot|verilog|json> [input file] [--swap <wire>,<wire>]...
+
+The input file defaults to input/2024/day24.txt.
+  --swap a,b   exchange the outputs of the gates driving a and b before exporting";
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2)
+}
+
+fn read_input(path: Option<&String>) -> String {
+    let path = path.map_or("input/2024/day24.txt", String::as_str);
+    fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Can't read {path}: {e}");
+        process::exit(1)
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(command) = args.first() else { usage() };
+    let format = match command.as_str() {
+        "dot" => Format::Dot,
+        "verilog" => Format::Verilog,
+        "json" => Format::Json,
+        _ => usage(),
+    };
+    let file = args.get(1).filter(|a| !a.starts_with("--"));
+    let mut options = args[1 + file.is_some() as usize..].iter();
+    let mut swaps = Vec::new();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--swap" => {
+                let swap = options.next().and_then(|s| s.split_once(','));
+                swaps.push(swap.unwrap_or_else(|| usage()));
+            }
+            _ => usage(),
+        }
+    }
+    let input = read_input(file);
+    match day24::export(&input, format, &swaps) {
+        Ok(output) => print!("{output}"),
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1)
+        }
+    }
+}
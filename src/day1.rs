@@ -1,5 +1,5 @@
+use crate::utils::parse::{integers_n, lines};
 use aoc_runner_derive::{aoc, aoc_generator};
-use prse::parse;
 use std::fmt::Display;
 // CodSpeed compatibility
 #[allow(dead_code)]
@@ -14,16 +14,11 @@ pub fn part2(input: &str) -> impl Display {
 
 #[aoc_generator(day1)]
 fn parse(input: &str) -> (Vec<i32>, Vec<i32>) {
-    let lines = input.lines();
-    let mut list1 = Vec::new();
-    let mut list2 = Vec::new();
-
-    for line in lines {
-        let (n1, n2): (i32, i32) = parse!(line, "{} {}");
-        list1.push(n1);
-        list2.push(n2);
-    }
-    (list1, list2)
+    lines(input, integers_n::<i32, 2>)
+        .unwrap_or_else(|e| panic!("Bad input at {e}"))
+        .into_iter()
+        .map(|[n1, n2]| (n1, n2))
+        .unzip()
 }
 
 #[aoc(day1, part1)]
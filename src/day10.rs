@@ -1,4 +1,5 @@
 use crate::utils::c2::{C2Field, C2};
+use crate::utils::parse::grid;
 use aoc_runner_derive::{aoc, aoc_generator};
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
@@ -17,10 +18,8 @@ pub fn part2(input: &str) -> impl Display {
 // CodSpeed compatibility end
 #[aoc_generator(day10)]
 fn parse(input: &str) -> C2Field<usize> {
-    let input = input.trim_end();
-    C2Field::from_string(input, |h| {
-        h.to_digit(10).expect("Should be a digit") as usize
-    })
+    grid(input, |_, h| h.to_digit(10).map(|h| h as usize))
+        .unwrap_or_else(|e| panic!("Bad input at {e}"))
 }
 
 #[inline(always)]
@@ -29,10 +28,9 @@ fn traverse_part_1(from: &C2, map: &C2Field<usize>) -> Vec<C2> {
         if *h == 9 {
             vec![*from]
         } else {
-            from.neighbors_4()
-                .iter()
-                .filter(|c| map.get(c) == Some(&(h + 1)))
-                .flat_map(|c| traverse_part_1(c, map))
+            map.neighbors4(from)
+                .filter(|c| map[*c] == h + 1)
+                .flat_map(|c| traverse_part_1(&c, map))
                 .collect()
         }
     } else {
@@ -61,10 +59,9 @@ fn traverse_part_2(from: &C2, map: &C2Field<usize>) -> usize {
         if *h == 9 {
             1
         } else {
-            from.neighbors_4()
-                .iter()
-                .filter(|c| map.get(c) == Some(&(h + 1)))
-                .map(|c| traverse_part_2(c, map))
+            map.neighbors4(from)
+                .filter(|c| map[*c] == h + 1)
+                .map(|c| traverse_part_2(&c, map))
                 .sum()
         }
     } else {
@@ -1,4 +1,4 @@
-use crate::utils::c2::{C2Field, C2, C2_DOWN, C2_LEFT, C2_RIGHT, C2_UP};
+use crate::utils::c2::{C2Field, Direction, DirectionSet, C2};
 use aoc_runner_derive::{aoc, aoc_generator};
 use fxhash::FxHashMap;
 use std::fmt::Display;
@@ -28,11 +28,9 @@ fn calculate_perimeter_and_area(
 ) -> (u32, u32) {
     let mut neighbors = 0;
     let mut values = values;
-    let letter = *map
-        .get(position)
-        .expect("Should not be called on non existing position");
+    let letter = map[*position];
     visited[map.indice(position)] = true;
-    for (d, _) in DIRECTIONS {
+    for d in Direction::iter4() {
         let next = *position + d;
         if map.get(&next) == Some(&letter) {
             if !visited[map.indice(&next)] {
@@ -58,37 +56,23 @@ fn part1_solution(input: &C2Field<char>) -> u32 {
     fence
 }
 
-const UP: u8 = 0b0001;
-const DOWN: u8 = 0b0010;
-const LEFT: u8 = 0b0100;
-const RIGHT: u8 = 0b1000;
-
-const DIRECTIONS: [(C2, u8); 4] = [
-    (C2_UP, UP),
-    (C2_LEFT, LEFT),
-    (C2_DOWN, DOWN),
-    (C2_RIGHT, RIGHT),
-];
 fn calculate_sides(
     map: &C2Field<char>,
-    perimeter: &mut Vec<(usize, u8)>,
+    perimeter: &mut Vec<(usize, DirectionSet)>,
     position: &C2,
     id: usize,
 ) -> u32 {
-    let letter = *map
-        .get(position)
-        .expect("Should not be called on non existing position");
-    let letter = Some(&letter);
+    let letter = Some(&map[*position]);
     let i = map.indice(position);
     perimeter[i].0 = id;
 
     let mut count = 1;
 
-    let mut walls = 0;
-    for (cd, d) in DIRECTIONS.iter() {
-        let next = *position + *cd;
+    let mut walls = DirectionSet::EMPTY;
+    for d in Direction::iter4() {
+        let next = *position + d;
         if map.get(&next) != letter {
-            walls |= d;
+            walls.insert(d);
         } else if perimeter[map.indice(&next)].0 == 0 {
             count += calculate_sides(map, perimeter, &next, id);
         }
@@ -97,7 +81,7 @@ fn calculate_sides(
     count
 }
 
-fn count_sides(sides: &[(usize, u8)], max_elements: usize, width: usize) -> Vec<u32> {
+fn count_sides(sides: &[(usize, DirectionSet)], max_elements: usize, width: usize) -> Vec<u32> {
     let height = sides.len() / width;
     let mut counts = vec![0u32; max_elements + 1];
     // horizontal
@@ -108,7 +92,7 @@ fn count_sides(sides: &[(usize, u8)], max_elements: usize, width: usize) -> Vec<
         for x in 0..width {
             let c = y * width + x;
             let (id, wall) = sides[c];
-            if wall & UP != 0 {
+            if wall.contains(Direction::Up) {
                 if up != id {
                     up = id;
                     counts[id] += 1;
@@ -116,7 +100,7 @@ fn count_sides(sides: &[(usize, u8)], max_elements: usize, width: usize) -> Vec<
             } else {
                 up = 0;
             }
-            if wall & DOWN != 0 {
+            if wall.contains(Direction::Down) {
                 if down != id {
                     down = id;
                     counts[id] += 1;
@@ -135,7 +119,7 @@ fn count_sides(sides: &[(usize, u8)], max_elements: usize, width: usize) -> Vec<
         for y in 0..height {
             let c = y * width + x;
             let (id, wall) = sides[c];
-            if wall & LEFT != 0 {
+            if wall.contains(Direction::Left) {
                 if left != id {
                     left = id;
                     counts[id] += 1;
@@ -143,7 +127,7 @@ fn count_sides(sides: &[(usize, u8)], max_elements: usize, width: usize) -> Vec<
             } else {
                 left = 0;
             }
-            if wall & RIGHT != 0 {
+            if wall.contains(Direction::Right) {
                 if right != id {
                     right = id;
                     counts[id] += 1;
@@ -162,7 +146,7 @@ fn part2_solution(input: &C2Field<char>) -> u32 {
     let mut sides = input
         .values()
         .iter()
-        .map(|_| (0usize, 0u8))
+        .map(|_| (0usize, DirectionSet::EMPTY))
         .collect::<Vec<_>>();
     let mut counts: FxHashMap<usize, u32> = FxHashMap::default();
     let keys = input.keys().clone();
@@ -1,3 +1,4 @@
+use crate::utils::num::{extended_gcd, solve_2x2, LinearSolution};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::fmt::Display;
@@ -60,24 +61,54 @@ fn parse(input: &str) -> Vec<ClawMachine> {
         .collect()
 }
 
+/// Cheapest non-negative `a`, `b` with `a * step_a + b * step_b = target`, for machines whose
+/// buttons move along the same line.
+fn cheapest_on_line(step_a: i64, step_b: i64, target: i64) -> Option<i128> {
+    let (g, x, y) = extended_gcd(step_a, step_b);
+    if g == 0 || target % g != 0 {
+        return None;
+    }
+    let scale = (target / g) as i128;
+    let (a0, b0) = (x as i128 * scale, y as i128 * scale);
+    let (da, db) = ((step_b / g) as i128, -(step_a / g) as i128);
+
+    let mut low = i128::MIN;
+    let mut high = i128::MAX;
+    for (v0, d) in [(a0, da), (b0, db)] {
+        match d.signum() {
+            1 => low = low.max((-v0).div_euclid(d) + ((-v0).rem_euclid(d) != 0) as i128),
+            -1 => high = high.min(v0.div_euclid(-d)),
+            _ if v0 < 0 => return None,
+            _ => {}
+        }
+    }
+    if low > high {
+        return None;
+    }
+
+    let k = if 3 * da + db > 0 || high == i128::MAX {
+        low
+    } else {
+        high
+    };
+    (k != i128::MIN).then(|| 3 * (a0 + k * da) + b0 + k * db)
+}
+
 fn solve(input: &[ClawMachine], offset: i64) -> i64 {
     input
         .iter()
         .map(|c| {
-            let ax = c.ax;
-            let ay = c.ay;
-            let bx = c.bx;
-            let by = c.by;
             let px = c.px + offset;
             let py = c.py + offset;
-            let b = (px * ay - py * ax) / (bx * ay - ax * by);
-            let bbx = b * bx;
-            let a = (px - bbx) / ax;
-            if px == a * ax + bbx && py == a * ay + b * by {
-                a * 3 + b
-            } else {
-                0
-            }
+            let cost = match solve_2x2([[c.ax, c.bx], [c.ay, c.by]], [px, py]) {
+                LinearSolution::Unique(a, b) if a >= 0 && b >= 0 => Some(a * 3 + b),
+                LinearSolution::Unique(..) | LinearSolution::None => None,
+                LinearSolution::Infinite if c.ax != 0 || c.bx != 0 => {
+                    cheapest_on_line(c.ax, c.bx, px)
+                }
+                LinearSolution::Infinite => cheapest_on_line(c.ay, c.by, py),
+            };
+            cost.map_or(0, |cost| cost as i64)
         })
         .sum()
 }
@@ -114,4 +145,20 @@ Prize: X=18641, Y=10279"#;
     fn part1_example() {
         assert_eq!(part1_solution(&parse(TEST_DATA)), 480);
     }
+
+    const COLINEAR_DATA: &str = r#"Button A: X+2, Y+4
+Button B: X+3, Y+6
+Prize: X=12, Y=24
+
+Button A: X+6, Y+3
+Button B: X+2, Y+1
+Prize: X=14, Y=7
+
+Button A: X+2, Y+2
+Button B: X+4, Y+4
+Prize: X=7, Y=7"#;
+    #[test]
+    fn colinear_buttons() {
+        assert_eq!(part1_solution(&parse(COLINEAR_DATA)), 4 + 7);
+    }
 }
@@ -1,6 +1,7 @@
+use crate::utils::c2::C2;
+use crate::utils::num::{crt, lcm};
+use crate::utils::parse::{integers_n, lines};
 use aoc_runner_derive::{aoc, aoc_generator};
-use rayon::iter::ParallelIterator;
-use rayon::prelude::IntoParallelRefIterator;
 use std::fmt::Display;
 // CodSpeed compatibility
 #[allow(dead_code)]
@@ -12,42 +13,28 @@ pub fn part2(input: &str) -> impl Display {
     part2_solution(&parse(input))
 }
 // CodSpeed compatibility end
-type Robot = (i32, i32, i32, i32);
+type Robot = (C2, C2);
 const W: i32 = 101;
 const H: i32 = 103;
-const TURNS: i32 = 100;
+const TURNS: i64 = 100;
 
 #[aoc_generator(day14)]
 fn parse(input: &str) -> Vec<Robot> {
-    input
-        .lines()
-        .filter_map(|line| {
-            if let Some(space) = line.find(' ') {
-                let p = &line[2..space];
-                let v = &line[(space + 3)..];
-                let pc = p.find(',').expect("Missing comma");
-                let vc = v.find(',').expect("Missing comma");
-                let x: i32 = p[..pc].parse().expect("Data error");
-                let y: i32 = p[pc + 1..].parse().expect("Data error");
-                let vx: i32 = v[..vc].parse().expect("Data error");
-                let vy: i32 = v[vc + 1..].parse().expect("Data error");
-
-                Some((x, vx, y, vy))
-            } else {
-                None
-            }
-        })
-        .collect()
+    lines(input, |line| {
+        let [x, y, vx, vy] = integers_n(line)?;
+        Ok((C2::new(x, y), C2::new(vx, vy)))
+    })
+    .unwrap_or_else(|e| panic!("Bad input at {e}"))
 }
 
 fn part1_solver(input: &[Robot], w: i32, h: i32) -> usize {
     let w_div = w / 2;
     let h_div = h / 2;
+    let size = C2::new(w, h);
 
     let mut q = [0, 0, 0, 0];
-    for robot in input.iter() {
-        let x = (robot.0 + (robot.1 + w) * TURNS) % w;
-        let y = (robot.2 + (robot.3 + h) * TURNS) % h;
+    for (position, velocity) in input.iter() {
+        let C2 { x, y } = position.step_wrapping(*velocity, TURNS, size);
 
         if x != w_div && y != h_div {
             let q_ind = if x < w_div { 1 } else { 0 } + if y < h_div { 2 } else { 0 };
@@ -63,32 +50,36 @@ fn part1_solution(input: &[Robot]) -> usize {
     part1_solver(input, W, H)
 }
 
+/// Sum of squared distances from the mean, scaled by the number of values.
+fn spread(values: impl Iterator<Item = i32>) -> i64 {
+    let (n, sum, sum_sq) = values.fold((0i64, 0i64, 0i64), |(n, s, sq), v| {
+        let v = v as i64;
+        (n + 1, s + v, sq + v * v)
+    });
+    n * sum_sq - sum * sum
+}
+
+/// Robots form the picture when both axes are at their tightest. Each axis repeats on its own
+/// with period `W` or `H`, so the best turn for each is found separately and combined with CRT.
 #[aoc(day14, part2)]
-fn part2_solution(input: &[Robot]) -> i32 {
-    let turns = (0..(W * H)).collect::<Vec<i32>>();
-    *turns
-        .par_iter()
-        .map(|turns| {
-            let mut seen_map = vec![false; (W * H) as usize];
-            let seen: usize = input
-                .iter()
-                .map(|robot| {
-                    let x = (robot.0 + (robot.1 + W) * turns) % W;
-                    let y = (robot.2 + (robot.3 + H) * turns) % H;
-                    let index = (x + y * W) as usize;
-                    if seen_map[index] {
-                        0
-                    } else {
-                        seen_map[index] = true;
-                        1
-                    }
-                })
-                .sum();
-            (turns, seen)
-        })
-        .max_by(|a, b| a.1.cmp(&b.1))
-        .expect("Data error")
-        .0
+fn part2_solution(input: &[Robot]) -> i64 {
+    let size = C2::new(W, H);
+    let positions = |turn: i64| {
+        input
+            .iter()
+            .map(move |(position, velocity)| position.step_wrapping(*velocity, turn, size))
+    };
+    let best_x = (0..W as i64)
+        .min_by_key(|t| spread(positions(*t).map(|c| c.x)))
+        .expect("Data error");
+    let best_y = (0..H as i64)
+        .min_by_key(|t| spread(positions(*t).map(|c| c.y)))
+        .expect("Data error");
+
+    let (turn, period) =
+        crt(&[(best_x, W as i64), (best_y, H as i64)]).expect("W and H are coprime");
+    debug_assert_eq!(Some(period), lcm(W as i64, H as i64));
+    turn
 }
 
 #[cfg(test)]
@@ -111,4 +102,17 @@ p=9,5 v=-3,-3"#;
     fn part1_example() {
         assert_eq!(part1_solver(&parse(EXAMPLE_DATA), 11, 7), 12);
     }
+
+    #[test]
+    fn part2_finds_clustered_turn() {
+        let size = C2::new(W, H);
+        let robots: Vec<Robot> = (0..40)
+            .map(|i| {
+                let velocity = C2::new((i * 37) % 201 - 100, (i * 53) % 199 - 99);
+                let picture = C2::new(40 + i % 6, 50 + i / 6);
+                (picture.step_wrapping(velocity, -1234, size), velocity)
+            })
+            .collect();
+        assert_eq!(part2_solution(&robots), 1234);
+    }
 }
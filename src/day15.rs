@@ -1,4 +1,4 @@
-use crate::utils::c2::{C2Field, C2, C2_DOWN, C2_LEFT, C2_RIGHT, C2_UP};
+use crate::utils::c2::{C2Field, Direction, C2, C2_LEFT, C2_RIGHT};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::fmt::Display;
 
@@ -30,13 +30,7 @@ fn parse(input: &str) -> Input {
     let map = C2Field::from_string(map, |c| c);
     let moves = moves
         .chars()
-        .filter_map(|c| match c {
-            '<' => Some(C2_LEFT),
-            '^' => Some(C2_UP),
-            '>' => Some(C2_RIGHT),
-            'v' => Some(C2_DOWN),
-            _ => None,
-        })
+        .filter_map(|c| Direction::from_arrow(c).map(C2::from))
         .collect::<Vec<_>>();
 
     (map, moves)
@@ -45,7 +39,7 @@ fn parse(input: &str) -> Input {
 fn can_move(map: &mut C2Field<char>, position: C2, direction: &C2) -> bool {
     let target = position + *direction;
 
-    let can_move = match *map.get(&target).expect("Should exist") {
+    let can_move = match map[target] {
         FLOOR => true,
         WALL => false,
         BOX => can_move(map, target, direction),
@@ -70,7 +64,7 @@ fn can_move(map: &mut C2Field<char>, position: C2, direction: &C2) -> bool {
     can_move
 }
 fn do_move(map: &mut C2Field<char>, position: C2, direction: &C2, sidestep: bool) {
-    let tile = *map.get(&position).expect("Should exist");
+    let tile = map[position];
 
     let target = position + *direction;
     match tile {
@@ -95,10 +89,9 @@ fn do_move(map: &mut C2Field<char>, position: C2, direction: &C2, sidestep: bool
         _ => panic!("Bad data"),
     }
 
-    let current = *map.get(&position).expect("Should exist");
-    let next = *map.get(&target).expect("Should exist");
-    map.set(&target, current);
-    map.set(&position, next);
+    let current = map[position];
+    map[position] = map[target];
+    map[target] = current;
 }
 
 fn perform_move(map: &mut C2Field<char>, position: C2, direction: &C2) -> bool {
@@ -113,7 +106,7 @@ fn solve(input: &Input) -> i32 {
     let mut map = input.0.clone();
 
     let mut robot = map.find_first(ROBOT).expect("No robot found");
-    map.set(&robot, FLOOR);
+    map[robot] = FLOOR;
 
     for direction in input.1.iter() {
         if perform_move(&mut map, robot, direction) {
@@ -1,5 +1,6 @@
-use crate::utils::c2::{C2Field, C2, C2_DOWN, C2_LEFT, C2_RIGHT, C2_UP};
-use crate::utils::maze::{parse_maze, Maze};
+use crate::utils::bitgrid::BitGrid;
+use crate::utils::c2::{C2, C2_DOWN, C2_LEFT, C2_RIGHT, C2_UP};
+use crate::utils::maze::{Maze, MazeSpec};
 use aoc_runner_derive::{aoc, aoc_generator};
 use binary_heap_plus::BinaryHeap;
 use fxhash::{FxHashMap, FxHashSet};
@@ -20,7 +21,10 @@ const PRICE_ROTATE: u32 = 1000;
 const PRICE_WALK: u32 = 1;
 #[aoc_generator(day16)]
 fn parse(input: &str) -> Maze {
-    parse_maze(input)
+    MazeSpec::start_end()
+        .parse(input)
+        .unwrap_or_else(|e| panic!("Bad input: {e}"))
+        .into_start_end()
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
@@ -30,7 +34,7 @@ struct Move {
 }
 
 impl Move {
-    fn get_possible_moves(&self, map: &C2Field<bool>) -> Vec<(Move, u32)> {
+    fn get_possible_moves(&self, map: &BitGrid) -> Vec<(Move, u32)> {
         let mut moves = Vec::with_capacity(3);
         moves.push((
             Move {
@@ -49,7 +53,7 @@ impl Move {
         ));
 
         let walk = self.position + self.direction;
-        if map.get(&walk) == Some(&true) {
+        if map.get(&walk) {
             moves.push((
                 Move {
                     position: walk,
@@ -61,7 +65,7 @@ impl Move {
         moves
     }
 
-    fn get_possible_reverse_moves(&self, map: &C2Field<bool>) -> Vec<(Move, u32)> {
+    fn get_possible_reverse_moves(&self, map: &BitGrid) -> Vec<(Move, u32)> {
         let mut moves = Vec::with_capacity(3);
         moves.push((
             Move {
@@ -80,7 +84,7 @@ impl Move {
         ));
 
         let walk = self.position - self.direction;
-        if map.get(&walk) == Some(&true) {
+        if map.get(&walk) {
             moves.push((
                 Move {
                     position: walk,
@@ -93,7 +97,7 @@ impl Move {
     }
 }
 
-fn solve(start: C2, map: &C2Field<bool>) -> FxHashMap<Move, u32> {
+fn solve(start: C2, map: &BitGrid) -> FxHashMap<Move, u32> {
     let direction = C2_RIGHT;
 
     let mut prices: FxHashMap<Move, u32> = FxHashMap::default();
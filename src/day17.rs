@@ -1,15 +1,38 @@
 use crate::day17::Opcode::{Adv, Bdv, Bst, Bxc, Bxl, Cdv, Jnz, Out};
+use crate::utils::parse::{integers, integers_n, key_value, ParseError, ParseResult};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
+mod asm;
+mod decoded;
+mod disasm;
+mod error;
+mod quine;
+mod search;
+mod trace;
+
+pub use asm::assemble;
+pub use decoded::DecodedProgram;
+pub use disasm::Disassembly;
+pub use error::{Limits, RunError};
+pub use quine::{QuineError, ShapeError};
+pub use search::{NoSolution, SearchSpace};
+pub use trace::{Breakpoint, Debugger, Stop, TraceStep};
+
 // CodSpeed compatibility
 #[allow(dead_code, clippy::useless_format)]
 pub fn part1(input: &str) -> String {
-    format!("{}", part1_solution(&parse(input)))
+    format!(
+        "{}",
+        part1_solution(&parse(input)).unwrap_or_else(|e| panic!("{e}"))
+    )
 }
 #[allow(dead_code, clippy::useless_format)]
 pub fn part2(input: &str) -> String {
-    format!("{}", part2_solution(&parse(input)))
+    format!(
+        "{}",
+        part2_solution(&parse(input)).unwrap_or_else(|e| panic!("{e}"))
+    )
 }
 // CodSpeed compatibility end
 
@@ -23,35 +46,46 @@ enum StepResult {
     None,
 }
 
-#[derive(Clone, Copy, Default)]
-struct Registers {
-    a: u64,
-    b: u64,
-    c: u64,
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub struct Registers {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
 }
+/// The 3-bit computer with its program loaded.
 #[derive(Clone)]
-struct Computer {
+pub struct Computer {
     registers: Registers,
     pc: usize,
     instructions: Vec<u8>,
+    limits: Limits,
 }
 
 impl Computer {
-    fn step(&mut self) -> Option<u8> {
-        let opcode: Opcode = Opcode::from(self.instructions[self.pc]);
-        let operand: u8 = self.instructions[self.pc + 1];
-        self.pc += 2;
+    /// Computer for a program in puzzle input format.
+    pub fn from_input(input: &str) -> Result<Self, ParseError> {
+        parse_computer(input)
+    }
 
-        let argument = if opcode.needs_operand_resolve() {
-            Operand::from(operand).resolve(&self.registers)
-        } else {
-            operand as u64
-        };
+    /// Replaces the step limit and division handling used by [`compute`] and the solvers.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    fn step(&mut self) -> Result<Option<u8>, RunError> {
+        let (opcode, argument) = self.decode()?;
+        if self.limits.strict_division && opcode.divides() && argument >= 64 {
+            return Err(RunError::DivisionOverflow {
+                pc: self.pc,
+                power: argument,
+            });
+        }
+        self.pc += 2;
 
         let result = opcode.execute(argument, &self.registers);
 
         match result {
-            StepResult::Out(out) => return Some(out),
+            StepResult::Out(out) => return Ok(Some(out)),
             StepResult::SetA(a) => {
                 self.registers.a = a;
             }
@@ -67,7 +101,28 @@ impl Computer {
             StepResult::None => {}
         }
 
-        None
+        Ok(None)
+    }
+
+    /// Instruction at `pc` and its argument, with combo operands resolved.
+    fn decode(&self) -> Result<(Opcode, u64), RunError> {
+        let pc = self.pc;
+        let opcode = self.instructions[pc];
+        let opcode = Opcode::decode(opcode).ok_or(RunError::InvalidOpcode { pc, opcode })?;
+        let &operand = self
+            .instructions
+            .get(pc + 1)
+            .ok_or(RunError::TruncatedInstruction { pc })?;
+        let argument = if opcode.needs_operand_resolve() {
+            Operand::decode(operand)
+                .ok_or(RunError::InvalidOperand { pc, operand })?
+                .resolve(&self.registers)
+        } else if operand > 7 {
+            return Err(RunError::InvalidOperand { pc, operand });
+        } else {
+            operand as u64
+        };
+        Ok((opcode, argument))
     }
 
     fn is_halted(&self) -> bool {
@@ -75,6 +130,12 @@ impl Computer {
     }
 }
 
+/// `a / 2^power` as a shift, 0 once the power reaches 64 instead of overflowing.
+#[inline]
+fn divide(a: u64, power: u64) -> u64 {
+    a.checked_shr(power.min(64) as u32).unwrap_or(0)
+}
+
 #[derive(Clone, Copy)]
 #[repr(u8)]
 enum Opcode {
@@ -89,16 +150,34 @@ enum Opcode {
 }
 impl Opcode {
     const fn from(opcode: u8) -> Self {
+        match Self::decode(opcode) {
+            Some(opcode) => opcode,
+            None => panic!("Unknown opcode"),
+        }
+    }
+    const fn decode(opcode: u8) -> Option<Self> {
         match opcode {
-            0 => Adv,
-            1 => Bxl,
-            2 => Bst,
-            3 => Jnz,
-            4 => Bxc,
-            5 => Out,
-            6 => Bdv,
-            7 => Cdv,
-            _ => panic!("Unknown opcode"),
+            0 => Some(Adv),
+            1 => Some(Bxl),
+            2 => Some(Bst),
+            3 => Some(Jnz),
+            4 => Some(Bxc),
+            5 => Some(Out),
+            6 => Some(Bdv),
+            7 => Some(Cdv),
+            _ => None,
+        }
+    }
+    const fn mnemonic(&self) -> &'static str {
+        match self {
+            Adv => "adv",
+            Bxl => "bxl",
+            Bst => "bst",
+            Jnz => "jnz",
+            Bxc => "bxc",
+            Out => "out",
+            Bdv => "bdv",
+            Cdv => "cdv",
         }
     }
     #[inline]
@@ -115,9 +194,13 @@ impl Opcode {
         }
     }
     #[inline]
+    fn divides(&self) -> bool {
+        matches!(self, Adv | Bdv | Cdv)
+    }
+    #[inline]
     fn execute(&self, argument: u64, registers: &Registers) -> StepResult {
         match self {
-            Adv => StepResult::SetA(registers.a / 2_u64.pow(argument as u32)),
+            Adv => StepResult::SetA(divide(registers.a, argument)),
             Bxl => StepResult::SetB(registers.b ^ argument),
             Bst => StepResult::SetB(argument % 8),
             Jnz => {
@@ -129,8 +212,8 @@ impl Opcode {
             }
             Bxc => StepResult::SetB(registers.b ^ registers.c),
             Out => StepResult::Out((argument % 8) as u8),
-            Bdv => StepResult::SetB(registers.a / 2_u64.pow(argument as u32)),
-            Cdv => StepResult::SetC(registers.a / 2_u64.pow(argument as u32)),
+            Bdv => StepResult::SetB(divide(registers.a, argument)),
+            Cdv => StepResult::SetC(divide(registers.a, argument)),
         }
     }
 }
@@ -148,16 +231,27 @@ enum Operand {
 }
 
 impl Operand {
-    const fn from(operand: u8) -> Self {
+    const fn decode(operand: u8) -> Option<Self> {
         match operand {
-            0 => Operand::Lit0,
-            1 => Operand::Lit1,
-            2 => Operand::Lit2,
-            3 => Operand::Lit3,
-            4 => Operand::A,
-            5 => Operand::B,
-            6 => Operand::C,
-            _ => panic!("Unknown operand"),
+            0 => Some(Operand::Lit0),
+            1 => Some(Operand::Lit1),
+            2 => Some(Operand::Lit2),
+            3 => Some(Operand::Lit3),
+            4 => Some(Operand::A),
+            5 => Some(Operand::B),
+            6 => Some(Operand::C),
+            _ => None,
+        }
+    }
+    const fn name(&self) -> &'static str {
+        match self {
+            Operand::Lit0 => "0",
+            Operand::Lit1 => "1",
+            Operand::Lit2 => "2",
+            Operand::Lit3 => "3",
+            Operand::A => "A",
+            Operand::B => "B",
+            Operand::C => "C",
         }
     }
     const fn resolve(&self, registers: &Registers) -> u64 {
@@ -173,71 +267,113 @@ impl Operand {
     }
 }
 
-#[aoc_generator(day17)]
-fn parse(input: &str) -> Computer {
-    let mut lines = input.lines();
-    let line_a = lines.next().expect("Bad input");
-    let _ = lines.next();
-    let _ = lines.next();
-    let _ = lines.next();
-    let program = lines.next().expect("Bad input");
-
-    Computer {
-        registers: Registers {
-            a: line_a[12..].parse::<u64>().expect("Bad input"),
-            ..Default::default()
-        },
+fn parse_computer(input: &str) -> ParseResult<Computer> {
+    let mut registers = Registers::default();
+    let mut instructions = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (key, value) = key_value(line, ":").map_err(|e| e.on_line(number + 1))?;
+        let register = match key {
+            "Register A" => &mut registers.a,
+            "Register B" => &mut registers.b,
+            "Register C" => &mut registers.c,
+            "Program" => {
+                instructions = integers(value).map_err(|e| e.on_line(number + 1))?;
+                continue;
+            }
+            _ => {
+                return Err(ParseError::new(
+                    number + 1,
+                    1,
+                    format!("Unknown key '{key}'"),
+                ))
+            }
+        };
+        let [v] = integers_n(value).map_err(|e| e.on_line(number + 1))?;
+        *register = v;
+    }
 
+    Ok(Computer {
+        registers,
         pc: 0,
-        instructions: program[9..]
-            .split(",")
-            .map(|n| n.parse::<u8>().expect("Bad input"))
-            .collect(),
-    }
+        instructions,
+        limits: Limits::default(),
+    })
+}
+
+/// Disassembly of the program in puzzle input format.
+pub fn disassemble(input: &str) -> Result<Disassembly, ParseError> {
+    parse_computer(input).map(|computer| Disassembly::new(&computer.instructions))
+}
+
+#[aoc_generator(day17)]
+fn parse(input: &str) -> Computer {
+    parse_computer(input).unwrap_or_else(|e| panic!("Bad input at {e}"))
 }
 
-fn compute<'a>(a: u64, computer: &Computer, output: &'a mut Vec<u8>) -> &'a mut Vec<u8> {
+fn compute<'a>(
+    a: u64,
+    computer: &Computer,
+    output: &'a mut Vec<u8>,
+) -> Result<&'a mut Vec<u8>, RunError> {
     output.clear();
     let mut computer = computer.clone();
     computer.registers.a = a;
 
+    let mut steps = 0;
     while !computer.is_halted() {
-        if let Some(result) = computer.step() {
+        if steps == computer.limits.max_steps {
+            return Err(RunError::StepLimit { steps });
+        }
+        steps += 1;
+        if let Some(result) = computer.step()? {
             output.push(result);
         }
     }
-    output
+    Ok(output)
 }
 #[aoc(day17, part1)]
-fn part1_solution(input: &Computer) -> String {
+fn part1_solution(input: &Computer) -> Result<String, RunError> {
     let mut output: Vec<u8> = Vec::with_capacity(input.instructions.len());
-    compute(input.registers.a, input, &mut output)
+    Ok(compute(input.registers.a, input, &mut output)?
         .iter()
-        .join(",")
+        .join(","))
 }
 
 #[aoc(day17, part2)]
-fn part2_solution(input: &Computer) -> u64 {
+fn part2_solution(input: &Computer) -> Result<u64, QuineError> {
+    quine::lowest_quine(input)
+}
+
+/// Values of `A` the heuristic runs at once while looking for the loop size or the lowest match.
+const HEURISTIC_BATCH: u64 = 64;
+
+#[aoc(day17, part2, Heuristic)]
+fn part2_solution_heuristic(input: &Computer) -> Result<u64, RunError> {
     let target = input.instructions.clone();
+    let program = DecodedProgram::from(input);
     let mut output: Vec<u8> = Vec::with_capacity(input.instructions.len());
 
-    let loop_size = {
-        let mut counter = 0u64;
-        while compute(counter, input, &mut output).len() < 2 {
-            counter += 1;
+    let mut start = 0;
+    let loop_size = loop {
+        let outputs = program.run_batch(start..start + HEURISTIC_BATCH)?;
+        if let Some(offset) = outputs.iter().position(|o| o.len() >= 2) {
+            break start + offset as u64;
         }
-        counter
+        start += HEURISTIC_BATCH;
     };
     let target_len = target.len();
 
     let mut a = loop_size.pow((target_len - 1) as u32);
 
-    while !target.eq(compute(a, input, &mut output)) {
+    while !target.eq(program.run(a, &mut output)?) {
         a += 1;
         let mut loops: i32 = (target_len - 1) as i32;
         a %= loop_size.pow(target_len as u32);
         while loops >= 0 {
-            compute(a, input, &mut output);
+            program.run(a, &mut output)?;
 
             if output[loops as usize] == target[loops as usize] {
                 loops -= 1;
@@ -250,11 +386,20 @@ fn part2_solution(input: &Computer) -> u64 {
         }
     }
 
-    while target.eq(compute(a - 1, input, &mut output)) {
-        a -= 1;
+    loop {
+        let below = (a.saturating_sub(HEURISTIC_BATCH)..a).rev();
+        let matching = program
+            .run_batch(below)?
+            .iter()
+            .take_while(|o| **o == target)
+            .count();
+        a -= matching as u64;
+        if matching < HEURISTIC_BATCH as usize {
+            break;
+        }
     }
 
-    a
+    Ok(a)
 }
 
 #[cfg(test)]
@@ -273,11 +418,68 @@ Register C: 0
 Program: 0,3,5,4,3,0"#;
     #[test]
     fn part1_example() {
-        assert_eq!(part1_solution(&parse(EXAMPLE)), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(
+            part1_solution(&parse(EXAMPLE)).unwrap(),
+            "4,6,3,5,6,3,5,2,1,0"
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2_solution(&parse(SELF_RETURNING_PROGRAM)), 117440);
+        assert_eq!(
+            part2_solution(&parse(SELF_RETURNING_PROGRAM)).unwrap(),
+            117440
+        );
+        assert_eq!(
+            part2_solution_heuristic(&parse(SELF_RETURNING_PROGRAM)),
+            Ok(117440)
+        );
+    }
+
+    #[test]
+    fn run_errors() {
+        let run = |program: &str| part1_solution(&parse(program)).unwrap_err();
+        assert_eq!(
+            run("Register A: 0\nProgram: 5,4,5,7"),
+            RunError::InvalidOperand { pc: 2, operand: 7 }
+        );
+        assert_eq!(
+            run("Register A: 0\nProgram: 5,4,9,0"),
+            RunError::InvalidOpcode { pc: 2, opcode: 9 }
+        );
+        assert_eq!(
+            run("Register A: 0\nProgram: 5,4,5"),
+            RunError::TruncatedInstruction { pc: 2 }
+        );
+        assert_eq!(
+            run("Register A: 1\nProgram: 3,0"),
+            RunError::StepLimit { steps: 10_000_000 }
+        );
+        assert_eq!(
+            RunError::InvalidOperand { pc: 2, operand: 7 }.to_string(),
+            "Invalid operand 7 at 2"
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let mut computer = parse("Register A: 1\nRegister B: 64\nProgram: 6,5,5,5");
+        assert_eq!(part1_solution(&computer), Ok("0".to_string()));
+        computer.set_limits(Limits {
+            max_steps: 1,
+            strict_division: true,
+        });
+        assert_eq!(
+            part1_solution(&computer),
+            Err(RunError::DivisionOverflow { pc: 0, power: 64 })
+        );
+        computer.set_limits(Limits {
+            max_steps: 1,
+            strict_division: false,
+        });
+        assert_eq!(
+            part1_solution(&computer),
+            Err(RunError::StepLimit { steps: 1 })
+        );
     }
 }
@@ -0,0 +1,283 @@
+use super::Opcode::{Bxc, Jnz};
+use super::{Computer, Limits, Opcode, Registers};
+use crate::utils::parse::{ParseError, ParseResult};
+use fxhash::FxHashMap;
+use itertools::Itertools;
+
+const OPCODES: [Opcode; 8] = [
+    Opcode::Adv,
+    Opcode::Bxl,
+    Opcode::Bst,
+    Opcode::Jnz,
+    Opcode::Bxc,
+    Opcode::Out,
+    Opcode::Bdv,
+    Opcode::Cdv,
+];
+
+/// One statement of the source, with the 1-based line and column of every token.
+struct Statement<'a> {
+    line: usize,
+    tokens: Vec<(usize, &'a str)>,
+}
+
+impl Statement<'_> {
+    fn error(&self, token: usize, message: impl Into<String>) -> ParseError {
+        let column = self.tokens.get(token).map_or(1, |t| t.0);
+        ParseError::new(self.line, column, message)
+    }
+}
+
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let code = line.split([';', '#']).next().unwrap_or("");
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, ch) in code.char_indices().chain([(code.len(), ' ')]) {
+        let separator = ch.is_whitespace() || ch == ',';
+        match (start, separator) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                tokens.push((s + 1, &code[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn number(statement: &Statement, token: usize, max: u64) -> ParseResult<u64> {
+    let (_, text) = statement.tokens[token];
+    text.parse::<u64>()
+        .ok()
+        .filter(|&n| n <= max)
+        .ok_or_else(|| {
+            statement.error(
+                token,
+                format!("Expected a number up to {max}, found '{text}'"),
+            )
+        })
+}
+
+/// Assembles mnemonic source into registers and program.
+///
+/// Source has one instruction per line (`bst A`, `bxl 5`, `jnz loop`), `name:` labels for jump
+/// targets, `.register A 729` for initial register values and `.byte n` for raw values.
+/// `;` and `#` start comments, a leading number is taken as the expected address, so the
+/// disassembler listing assembles back.
+pub(super) fn assemble_program(source: &str) -> ParseResult<(Registers, Vec<u8>)> {
+    let mut labels: FxHashMap<&str, usize> = FxHashMap::default();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (i, line) in source.lines().enumerate() {
+        let mut statement = Statement {
+            line: i + 1,
+            tokens: tokens(line),
+        };
+        if statement
+            .tokens
+            .first()
+            .is_some_and(|(_, t)| t.chars().all(|c| c.is_ascii_digit()))
+        {
+            let expected = number(&statement, 0, u64::MAX)? as usize;
+            if expected != address {
+                return Err(statement.error(0, format!("Address {expected}, expected {address}")));
+            }
+            statement.tokens.remove(0);
+        }
+        while let Some(name) = statement
+            .tokens
+            .first()
+            .and_then(|(_, t)| t.strip_suffix(':'))
+        {
+            if labels.insert(name, address).is_some() {
+                return Err(statement.error(0, format!("Duplicate label '{name}'")));
+            }
+            statement.tokens.remove(0);
+        }
+        match statement.tokens.first() {
+            None => continue,
+            Some((_, ".register")) => {}
+            Some((_, ".byte")) => address += statement.tokens.len() - 1,
+            Some(_) => address += 2,
+        }
+        statements.push(statement);
+    }
+
+    let mut registers = Registers::default();
+    let mut program = Vec::with_capacity(address);
+    for statement in statements {
+        let (_, head) = statement.tokens[0];
+        let arguments = statement.tokens.len() - 1;
+        match head {
+            ".register" => {
+                if arguments != 2 {
+                    return Err(statement.error(0, "Expected '.register <A|B|C> <value>'"));
+                }
+                let value = number(&statement, 2, u64::MAX)?;
+                match statement.tokens[1].1 {
+                    "A" => registers.a = value,
+                    "B" => registers.b = value,
+                    "C" => registers.c = value,
+                    other => return Err(statement.error(1, format!("Unknown register '{other}'"))),
+                }
+            }
+            ".byte" => {
+                for token in 1..=arguments {
+                    program.push(number(&statement, token, u8::MAX as u64)? as u8);
+                }
+            }
+            mnemonic => {
+                let opcode = OPCODES
+                    .into_iter()
+                    .find(|o| o.mnemonic() == mnemonic)
+                    .ok_or_else(|| statement.error(0, format!("Unknown mnemonic '{mnemonic}'")))?;
+                let operand = match (arguments, opcode) {
+                    (0, Bxc) => 0,
+                    (0, _) => {
+                        return Err(statement.error(0, format!("'{mnemonic}' needs an operand")))
+                    }
+                    (1, _) => operand(&statement, opcode, &labels)?,
+                    _ => return Err(statement.error(2, "Unexpected operand")),
+                };
+                program.extend([opcode as u8, operand]);
+            }
+        }
+    }
+
+    Ok((registers, program))
+}
+
+fn operand(
+    statement: &Statement,
+    opcode: Opcode,
+    labels: &FxHashMap<&str, usize>,
+) -> ParseResult<u8> {
+    let (_, text) = statement.tokens[1];
+    let register = match text {
+        "A" => Some(4),
+        "B" => Some(5),
+        "C" => Some(6),
+        _ => None,
+    };
+    match (register, opcode) {
+        (Some(r), _) if opcode.needs_operand_resolve() => Ok(r),
+        (Some(_), _) => Err(statement.error(
+            1,
+            format!("'{}' takes a literal operand", opcode.mnemonic()),
+        )),
+        (None, Jnz) if !text.starts_with(|c: char| c.is_ascii_digit()) => {
+            let target = *labels
+                .get(text)
+                .ok_or_else(|| statement.error(1, format!("Unknown label '{text}'")))?;
+            if target > 7 {
+                return Err(statement.error(
+                    1,
+                    format!("Label '{text}' at {target} is out of jump range"),
+                ));
+            }
+            Ok(target as u8)
+        }
+        (None, _) => number(statement, 1, 7).map(|n| n as u8),
+    }
+}
+
+/// Assembles mnemonic source into the puzzle input format.
+pub fn assemble(source: &str) -> Result<String, ParseError> {
+    let (registers, program) = assemble_program(source)?;
+    Ok(format!(
+        "Register A: {}\nRegister B: {}\nRegister C: {}\n\nProgram: {}\n",
+        registers.a,
+        registers.b,
+        registers.c,
+        program.iter().join(",")
+    ))
+}
+
+impl Computer {
+    /// Computer loaded from mnemonic source, see [`assemble`].
+    pub fn assemble(source: &str) -> ParseResult<Self> {
+        let (registers, instructions) = assemble_program(source)?;
+        Ok(Self {
+            registers,
+            pc: 0,
+            instructions,
+            limits: Limits::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{compute, parse, Computer, Disassembly};
+    use super::*;
+
+    const SOURCE: &str = "
+.register A 729      ; initial value
+loop:
+    adv 1
+    out A            # print lowest bits
+    jnz loop
+";
+
+    #[test]
+    fn assembles_example() {
+        assert_eq!(
+            assemble(SOURCE).unwrap(),
+            "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n"
+        );
+        let computer = Computer::assemble(SOURCE).unwrap();
+        let mut output = Vec::new();
+        compute(729, &computer, &mut output).unwrap();
+        assert_eq!(output, vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+    }
+
+    #[test]
+    fn listing_round_trip() {
+        let programs: [&[u8]; 2] = [
+            &[2, 4, 1, 5, 7, 5, 4, 3, 0, 3, 5, 5, 3, 0, 1, 7],
+            &[9, 1, 1, 12, 3, 40, 5, 7, 3],
+        ];
+        for program in programs {
+            let listing = Disassembly::new(program).listing();
+            let (_, assembled) = assemble_program(&listing).unwrap();
+            assert_eq!(assembled, program);
+            let input = assemble(&listing).unwrap();
+            assert_eq!(parse(&input).instructions, program);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source| assemble_program(source).err().map(|e| e.to_string());
+        assert_eq!(
+            error("adv 1\nfoo 3"),
+            Some("2:1: Unknown mnemonic 'foo'".to_string())
+        );
+        assert_eq!(
+            error("  bxl A"),
+            Some("1:7: 'bxl' takes a literal operand".to_string())
+        );
+        assert_eq!(
+            error("out 8"),
+            Some("1:5: Expected a number up to 7, found '8'".to_string())
+        );
+        assert_eq!(
+            error("jnz nowhere"),
+            Some("1:5: Unknown label 'nowhere'".to_string())
+        );
+        assert_eq!(
+            error("a:\na:"),
+            Some("2:1: Duplicate label 'a'".to_string())
+        );
+        assert_eq!(
+            error("adv 1\nadv 1\nadv 1\nadv 1\nfar: out A\njnz far"),
+            Some("6:5: Label 'far' at 8 is out of jump range".to_string())
+        );
+        assert_eq!(
+            error("out"),
+            Some("1:1: 'out' needs an operand".to_string())
+        );
+    }
+}
@@ -0,0 +1,312 @@
+use super::{divide, Computer, Limits, Opcode, Operand, Registers, RunError};
+use rayon::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+enum Combo {
+    Literal(u64),
+    A,
+    B,
+    C,
+}
+
+impl Combo {
+    #[inline(always)]
+    fn value(self, registers: &Registers) -> u64 {
+        match self {
+            Combo::Literal(value) => value,
+            Combo::A => registers.a,
+            Combo::B => registers.b,
+            Combo::C => registers.c,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Instruction {
+    Adv(Combo),
+    Bxl(u64),
+    Bst(Combo),
+    Jnz(usize),
+    Bxc,
+    Out(Combo),
+    Bdv(Combo),
+    Cdv(Combo),
+    /// Reported when reached, so broken code that never runs doesn't matter.
+    Invalid(RunError),
+}
+
+impl Instruction {
+    fn decode(pc: usize, opcode: u8, operand: Option<u8>) -> Self {
+        let Some(decoded) = Opcode::decode(opcode) else {
+            return Instruction::Invalid(RunError::InvalidOpcode { pc, opcode });
+        };
+        let Some(operand) = operand else {
+            return Instruction::Invalid(RunError::TruncatedInstruction { pc });
+        };
+        let opcode = decoded;
+        let combo = match Operand::decode(operand) {
+            Some(Operand::A) => Combo::A,
+            Some(Operand::B) => Combo::B,
+            Some(Operand::C) => Combo::C,
+            Some(literal) => Combo::Literal(literal as u64),
+            None if opcode.needs_operand_resolve() || operand > 7 => {
+                return Instruction::Invalid(RunError::InvalidOperand { pc, operand })
+            }
+            None => Combo::Literal(operand as u64),
+        };
+        match opcode {
+            Opcode::Adv => Instruction::Adv(combo),
+            Opcode::Bxl => Instruction::Bxl(operand as u64),
+            Opcode::Bst => Instruction::Bst(combo),
+            Opcode::Jnz => Instruction::Jnz(operand as usize),
+            Opcode::Bxc => Instruction::Bxc,
+            Opcode::Out => Instruction::Out(combo),
+            Opcode::Bdv => Instruction::Bdv(combo),
+            Opcode::Cdv => Instruction::Cdv(combo),
+        }
+    }
+}
+
+/// Program decoded once up front, for running many initial values quickly.
+///
+/// Every byte address is decoded, so jumps to odd addresses behave like in [`Computer`].
+#[derive(Clone, Debug)]
+pub struct DecodedProgram {
+    instructions: Vec<Instruction>,
+    registers: Registers,
+    limits: Limits,
+}
+
+impl From<&Computer> for DecodedProgram {
+    fn from(computer: &Computer) -> Self {
+        let program = &computer.instructions;
+        Self {
+            instructions: (0..program.len())
+                .map(|pc| Instruction::decode(pc, program[pc], program.get(pc + 1).copied()))
+                .collect(),
+            registers: computer.registers,
+            limits: computer.limits,
+        }
+    }
+}
+
+impl DecodedProgram {
+    /// `a / 2^power`, or an error for large powers when division is strict.
+    #[inline(always)]
+    fn divide(&self, pc: usize, a: u64, power: u64) -> Result<u64, RunError> {
+        if self.limits.strict_division && power >= 64 {
+            Err(RunError::DivisionOverflow { pc, power })
+        } else {
+            Ok(divide(a, power))
+        }
+    }
+
+    /// Runs from `registers`, calling `out` for every output value until it returns `false`.
+    #[inline]
+    fn execute<F>(&self, mut registers: Registers, mut out: F) -> Result<(), RunError>
+    where
+        F: FnMut(u8) -> bool,
+    {
+        let mut pc = 0;
+        let mut steps = 0;
+        while let Some(instruction) = self.instructions.get(pc) {
+            if steps == self.limits.max_steps {
+                return Err(RunError::StepLimit { steps });
+            }
+            steps += 1;
+            let at = pc;
+            pc += 2;
+            match *instruction {
+                Instruction::Adv(combo) => {
+                    registers.a = self.divide(at, registers.a, combo.value(&registers))?
+                }
+                Instruction::Bxl(literal) => registers.b ^= literal,
+                Instruction::Bst(combo) => registers.b = combo.value(&registers) % 8,
+                Instruction::Jnz(target) => {
+                    if registers.a != 0 {
+                        pc = target;
+                    }
+                }
+                Instruction::Bxc => registers.b ^= registers.c,
+                Instruction::Out(combo) => {
+                    if !out((combo.value(&registers) % 8) as u8) {
+                        return Ok(());
+                    }
+                }
+                Instruction::Bdv(combo) => {
+                    registers.b = self.divide(at, registers.a, combo.value(&registers))?
+                }
+                Instruction::Cdv(combo) => {
+                    registers.c = self.divide(at, registers.a, combo.value(&registers))?
+                }
+                Instruction::Invalid(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    /// Output when starting with `registers`.
+    pub fn run_registers<'a>(
+        &self,
+        registers: Registers,
+        output: &'a mut Vec<u8>,
+    ) -> Result<&'a mut Vec<u8>, RunError> {
+        output.clear();
+        self.execute(registers, |value| {
+            output.push(value);
+            true
+        })?;
+        Ok(output)
+    }
+
+    /// Output when starting with `a` and the loaded `B` and `C`.
+    pub fn run<'a>(&self, a: u64, output: &'a mut Vec<u8>) -> Result<&'a mut Vec<u8>, RunError> {
+        self.run_registers(
+            Registers {
+                a,
+                ..self.registers
+            },
+            output,
+        )
+    }
+
+    /// Whether starting with `a` outputs exactly `target`, stopping at the first difference.
+    /// Runs that fail count as not matching.
+    pub fn outputs(&self, a: u64, target: &[u8]) -> bool {
+        self.outputs_registers(
+            Registers {
+                a,
+                ..self.registers
+            },
+            target,
+        )
+    }
+
+    /// [`Self::outputs`] starting with all of `registers`.
+    pub fn outputs_registers(&self, registers: Registers, target: &[u8]) -> bool {
+        let mut produced = 0;
+        let mut matches = true;
+        let result = self.execute(registers, |value| {
+            matches = target.get(produced) == Some(&value);
+            produced += 1;
+            matches
+        });
+        result.is_ok() && matches && produced == target.len()
+    }
+
+    /// Outputs for each of `a_values`, in order, or the first error.
+    pub fn run_batch<I>(&self, a_values: I) -> Result<Vec<Vec<u8>>, RunError>
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        a_values
+            .into_iter()
+            .map(|a| {
+                let mut output = Vec::new();
+                self.run(a, &mut output)?;
+                Ok(output)
+            })
+            .collect()
+    }
+
+    /// [`Self::run_batch`] spread over the rayon pool.
+    pub fn par_run_batch(&self, a_values: &[u64]) -> Result<Vec<Vec<u8>>, RunError> {
+        a_values
+            .par_iter()
+            .map(|&a| {
+                let mut output = Vec::new();
+                self.run(a, &mut output)?;
+                Ok(output)
+            })
+            .collect()
+    }
+
+    /// Every `a` in `a_values` that outputs exactly `target`, checked in parallel.
+    pub fn par_find(&self, a_values: std::ops::Range<u64>, target: &[u8]) -> Vec<u64> {
+        a_values
+            .into_par_iter()
+            .filter(|&a| self.outputs(a, target))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{compute, parse};
+    use super::*;
+
+    const PROGRAM: &str = r#"Register A: 0
+Register B: 0
+Register C: 0
+
+Program: 2,4,1,5,7,5,4,3,0,3,1,6,5,5,3,0"#;
+
+    #[test]
+    fn matches_interpreter() {
+        let computer = parse(PROGRAM);
+        let decoded = DecodedProgram::from(&computer);
+        let values: Vec<u64> = (0..2000).map(|i| i * 7919 + (i << 40)).collect();
+        let batch = decoded.run_batch(values.iter().copied()).unwrap();
+        assert_eq!(batch, decoded.par_run_batch(&values).unwrap());
+        let mut output = Vec::new();
+        for (a, expected) in values.iter().zip(&batch) {
+            assert_eq!(compute(*a, &computer, &mut output).unwrap(), expected);
+            assert!(decoded.outputs(*a, expected));
+        }
+    }
+
+    #[test]
+    fn large_shifts_saturate() {
+        let computer = Computer::assemble(
+            ".register B 70
+            bdv B
+            cdv 3
+            adv C
+            out B
+            out C
+            out A",
+        )
+        .unwrap();
+        let decoded = DecodedProgram::from(&computer);
+        let mut output = Vec::new();
+        decoded.run(u64::MAX, &mut output).unwrap();
+        assert_eq!(output, vec![0, 7, 0]);
+        assert_eq!(
+            compute(u64::MAX, &computer, &mut Vec::new()).unwrap(),
+            &output
+        );
+    }
+
+    #[test]
+    fn reports_errors_when_reached() {
+        // The invalid `out 7` is jumped over unless A is 0
+        let mut computer = Computer::assemble("jnz 4\nout 7\nout 0").unwrap();
+        let decoded = DecodedProgram::from(&computer);
+        let mut output = Vec::new();
+        assert_eq!(decoded.run(1, &mut output), Ok(&mut vec![0]));
+        let error = RunError::InvalidOperand { pc: 2, operand: 7 };
+        assert_eq!(decoded.run(0, &mut output), Err(error));
+        assert_eq!(compute(0, &computer, &mut output), Err(error));
+        assert!(!decoded.outputs(0, &[]));
+
+        computer.set_limits(Limits {
+            max_steps: 1,
+            strict_division: false,
+        });
+        let decoded = DecodedProgram::from(&computer);
+        assert_eq!(
+            decoded.run(1, &mut output),
+            Err(RunError::StepLimit { steps: 1 })
+        );
+    }
+
+    #[test]
+    fn finds_in_parallel() {
+        let decoded = DecodedProgram::from(&parse(PROGRAM));
+        let mut target = Vec::new();
+        decoded.run(123456, &mut target).unwrap();
+        let found = decoded.par_find(0..200_000, &target);
+        assert!(found.contains(&123456));
+        assert!(found.iter().all(|&a| decoded.outputs(a, &target)));
+    }
+}
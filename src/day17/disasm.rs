@@ -0,0 +1,233 @@
+use super::Opcode::{Adv, Bdv, Bst, Bxc, Bxl, Cdv, Jnz, Out};
+use super::{Opcode, Operand, RunError};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    address: usize,
+    opcode: u8,
+    // None for a trailing opcode without an operand
+    operand: Option<u8>,
+    /// Error the computer stops with when it reaches this line.
+    error: Option<RunError>,
+}
+
+/// Decoded program, printable as a mnemonic listing or as pseudocode.
+#[derive(Clone, Debug)]
+pub struct Disassembly {
+    lines: Vec<Line>,
+    labels: BTreeSet<usize>,
+}
+
+pub(super) fn label(address: usize) -> String {
+    format!("L{address}")
+}
+
+/// Mnemonic form of a single instruction, jump targets as plain addresses. Values the
+/// mnemonics can't express are written as `.byte`.
+pub(super) fn instruction(opcode: u8, operand: u8) -> String {
+    if opcode > 7 || operand > 7 {
+        return format!(".byte {}, {}", opcode, operand);
+    }
+    let opcode = Opcode::from(opcode);
+    let argument = match opcode {
+        Bxc if operand == 0 => return opcode.mnemonic().to_string(),
+        _ if opcode.needs_operand_resolve() => {
+            Operand::decode(operand).map_or(operand.to_string(), |o| o.name().to_string())
+        }
+        _ => operand.to_string(),
+    };
+    format!("{} {}", opcode.mnemonic(), argument)
+}
+
+/// Error running the instruction at `pc`, like [`super::Computer`] would report it.
+fn line_error(pc: usize, opcode: u8, operand: Option<u8>) -> Option<RunError> {
+    let Some(decoded) = Opcode::decode(opcode) else {
+        return Some(RunError::InvalidOpcode { pc, opcode });
+    };
+    let Some(operand) = operand else {
+        return Some(RunError::TruncatedInstruction { pc });
+    };
+    let limit = if decoded.needs_operand_resolve() {
+        6
+    } else {
+        7
+    };
+    (operand > limit).then_some(RunError::InvalidOperand { pc, operand })
+}
+
+impl Disassembly {
+    pub fn new(program: &[u8]) -> Self {
+        let lines: Vec<Line> = program
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let pc = i * 2;
+                let (opcode, operand) = (pair[0], pair.get(1).copied());
+                Line {
+                    address: pc,
+                    opcode,
+                    operand,
+                    error: line_error(pc, opcode, operand),
+                }
+            })
+            .collect();
+        let labels = lines
+            .iter()
+            .filter(|l| l.opcode == Jnz as u8)
+            .filter_map(|l| l.operand.map(usize::from))
+            .filter(|&target| target % 2 == 0 && target < program.len())
+            .collect();
+        Self { lines, labels }
+    }
+
+    fn jump_target(&self, target: u8) -> String {
+        if self.labels.contains(&(target as usize)) {
+            label(target as usize)
+        } else {
+            target.to_string()
+        }
+    }
+
+    /// Mnemonic form of one instruction, as accepted by the assembler.
+    fn mnemonic(&self, line: &Line) -> String {
+        match line.operand {
+            None => format!(".byte {}", line.opcode),
+            Some(operand) if line.opcode == Jnz as u8 && operand <= 7 => {
+                format!("jnz {}", self.jump_target(operand))
+            }
+            Some(operand) => instruction(line.opcode, operand),
+        }
+    }
+
+    /// What one instruction does, or why it can't run.
+    fn statement(&self, line: &Line) -> String {
+        match line.error {
+            Some(RunError::TruncatedInstruction { .. }) => {
+                return "!! truncated instruction, stops with an error".to_string()
+            }
+            Some(RunError::InvalidOpcode { opcode, .. }) => {
+                return format!("!! invalid opcode {opcode}")
+            }
+            Some(RunError::InvalidOperand { operand, .. }) => {
+                return format!("!! invalid operand {operand}")
+            }
+            _ => {}
+        }
+        let operand = line.operand.expect("Checked for truncation");
+        let opcode = Opcode::from(line.opcode);
+        let combo = Operand::decode(operand).map_or("", |o| o.name());
+        match opcode {
+            Adv => format!("A = A >> {combo}"),
+            Bxl => format!("B = B ^ {operand}"),
+            Bst => format!("B = {combo} % 8"),
+            Jnz if self.labels.contains(&(operand as usize)) => {
+                format!("if A != 0 goto {}", label(operand as usize))
+            }
+            Jnz if (operand as usize) < self.lines.len() * 2 => {
+                format!("if A != 0 goto {operand} (misaligned)")
+            }
+            Jnz => format!("if A != 0 goto {operand} (past the end, halts)"),
+            Bxc => "B = B ^ C".to_string(),
+            Out => format!("out {combo} % 8"),
+            Bdv => format!("B = A >> {combo}"),
+            Cdv => format!("C = A >> {combo}"),
+        }
+    }
+
+    /// Listing with addresses, labels and a pseudocode comment per instruction.
+    pub fn listing(&self) -> String {
+        let mut output = String::new();
+        for line in &self.lines {
+            if self.labels.contains(&line.address) {
+                output.push_str(&format!("{}:\n", label(line.address)));
+            }
+            output.push_str(&format!(
+                "{:>4}    {:<12}; {}\n",
+                line.address,
+                self.mnemonic(line),
+                self.statement(line)
+            ));
+        }
+        output
+    }
+
+    /// Program as pseudocode statements only.
+    pub fn pseudocode(&self) -> String {
+        let mut output = String::new();
+        for line in &self.lines {
+            if self.labels.contains(&line.address) {
+                output.push_str(&format!("{}:\n", label(line.address)));
+            }
+            output.push_str(&format!("    {}\n", self.statement(line)));
+        }
+        output
+    }
+
+    /// Whether any instruction would stop the computer with an error.
+    pub fn has_invalid(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Errors of the instructions that can't run, in address order.
+    pub fn errors(&self) -> impl Iterator<Item = RunError> + '_ {
+        self.lines.iter().filter_map(|l| l.error)
+    }
+}
+
+impl Display for Disassembly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.listing())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing() {
+        let disassembly = Disassembly::new(&[2, 4, 1, 5, 7, 5, 4, 0, 0, 3, 5, 5, 3, 0]);
+        assert_eq!(
+            disassembly.listing(),
+            "L0:
+   0    bst A       ; B = A % 8
+   2    bxl 5       ; B = B ^ 5
+   4    cdv B       ; C = A >> B
+   6    bxc         ; B = B ^ C
+   8    adv 3       ; A = A >> 3
+  10    out B       ; out B % 8
+  12    jnz L0      ; if A != 0 goto L0
+"
+        );
+        assert!(!disassembly.has_invalid());
+    }
+
+    #[test]
+    fn invalid_instructions() {
+        let disassembly = Disassembly::new(&[5, 7, 3, 5, 1, 7, 0]);
+        assert_eq!(
+            disassembly.pseudocode(),
+            "    !! invalid operand 7
+    if A != 0 goto 5 (misaligned)
+    B = B ^ 7
+    !! truncated instruction, stops with an error
+"
+        );
+        assert_eq!(disassembly.mnemonic(&disassembly.lines[0]), "out 7");
+        assert_eq!(
+            disassembly.errors().collect::<Vec<_>>(),
+            vec![
+                RunError::InvalidOperand { pc: 0, operand: 7 },
+                RunError::TruncatedInstruction { pc: 6 }
+            ]
+        );
+        let disassembly = Disassembly::new(&[9, 1, 3, 12]);
+        assert_eq!(
+            disassembly.pseudocode(),
+            "    !! invalid opcode 9\n    !! invalid operand 12\n"
+        );
+        assert_eq!(disassembly.mnemonic(&disassembly.lines[1]), ".byte 3, 12");
+    }
+}
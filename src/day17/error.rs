@@ -0,0 +1,55 @@
+use std::fmt::{Display, Formatter};
+
+/// Why a program stopped without halting normally.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum RunError {
+    /// Opcode above 7.
+    InvalidOpcode { pc: usize, opcode: u8 },
+    /// Combo operand 7, or above 7 for any instruction.
+    InvalidOperand { pc: usize, operand: u8 },
+    /// Opcode in the last position, without an operand after it.
+    TruncatedInstruction { pc: usize },
+    /// Still running after this many steps.
+    StepLimit { steps: usize },
+    /// Shift by 64 or more with [`Limits::strict_division`] set.
+    DivisionOverflow { pc: usize, power: u64 },
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::InvalidOpcode { pc, opcode } => {
+                write!(f, "Invalid opcode {opcode} at {pc}")
+            }
+            RunError::InvalidOperand { pc, operand } => {
+                write!(f, "Invalid operand {operand} at {pc}")
+            }
+            RunError::TruncatedInstruction { pc } => {
+                write!(f, "Instruction at {pc} has no operand")
+            }
+            RunError::StepLimit { steps } => write!(f, "Still running after {steps} steps"),
+            RunError::DivisionOverflow { pc, power } => {
+                write!(f, "Division by 2^{power} at {pc}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+/// Guards for running untrusted programs.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct Limits {
+    pub max_steps: usize,
+    /// Report shifts by 64 or more instead of saturating the result to 0.
+    pub strict_division: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_steps: 10_000_000,
+            strict_division: false,
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use super::Opcode::{Adv, Bdv, Bst, Bxc, Bxl, Cdv, Jnz, Out};
+use super::{Computer, DecodedProgram, Opcode, Operand};
+use std::fmt::{Display, Formatter};
+
+/// Why a program doesn't have the shape the backward search relies on.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ShapeError {
+    /// The program has an odd number of values, an opcode above 7 or an invalid operand.
+    NotDecodable,
+    /// `adv` must appear exactly once, found this many times.
+    AdvCount(usize),
+    /// The only `adv` has to shift by the literal 3, it uses this operand.
+    AdvNotThree(u8),
+    /// `out` must appear exactly once, found this many times.
+    OutCount(usize),
+    /// The program has to end with `jnz 0` and have no other jumps.
+    NoLoop,
+    /// This register is read before the pass sets it, so it carries over from the last pass.
+    CarriedRegister(char),
+}
+
+impl Display for ShapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShapeError::NotDecodable => write!(f, "Program doesn't decode into instructions"),
+            ShapeError::AdvCount(n) => write!(f, "Expected a single adv, found {n}"),
+            ShapeError::AdvNotThree(o) => write!(f, "Expected adv 3, found operand {o}"),
+            ShapeError::OutCount(n) => write!(f, "Expected a single out, found {n}"),
+            ShapeError::NoLoop => write!(f, "Expected one loop ending in jnz 0"),
+            ShapeError::CarriedRegister(r) => {
+                write!(f, "Register {r} is read before the loop sets it from A")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Why part 2 has no answer.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum QuineError {
+    Shape(ShapeError),
+    /// The backward search ran, but no `A` makes the program output itself.
+    NoQuine,
+}
+
+impl Display for QuineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuineError::Shape(e) => write!(f, "Program doesn't fit the backward search: {e}"),
+            QuineError::NoQuine => write!(f, "No A makes the program output itself"),
+        }
+    }
+}
+
+impl std::error::Error for QuineError {}
+
+/// Checks that the program is one loop which drops the lowest 3 bits of `A` and outputs one
+/// value per pass, so every output value depends on a 3-bit group of the initial `A`.
+pub(super) fn check_shape(program: &[u8]) -> Result<(), ShapeError> {
+    let decodable = |i: &[u8]| match Opcode::decode(i[0]) {
+        Some(opcode) if opcode.needs_operand_resolve() => i[1] < 7,
+        Some(_) => i[1] <= 7,
+        None => false,
+    };
+    if !program.len().is_multiple_of(2) || !program.chunks(2).all(decodable) {
+        return Err(ShapeError::NotDecodable);
+    }
+    let count = |opcode: Opcode| program.chunks(2).filter(|i| i[0] == opcode as u8).count();
+
+    let advs = count(Adv);
+    if advs != 1 {
+        return Err(ShapeError::AdvCount(advs));
+    }
+    let adv = program
+        .chunks(2)
+        .find(|i| i[0] == Adv as u8)
+        .expect("Counted");
+    if adv[1] != 3 {
+        return Err(ShapeError::AdvNotThree(adv[1]));
+    }
+    let outs = count(Out);
+    if outs != 1 {
+        return Err(ShapeError::OutCount(outs));
+    }
+    if count(Jnz) != 1 || program[program.len() - 2..] != [Jnz as u8, 0] {
+        return Err(ShapeError::NoLoop);
+    }
+    check_registers(program)
+}
+
+/// Checks that every pass sets `B` and `C` from `A` or literals before reading them, so each
+/// pass only depends on `A` and the search can run a 3-bit group with any `B` and `C`.
+fn check_registers(program: &[u8]) -> Result<(), ShapeError> {
+    let (mut b_set, mut c_set) = (false, false);
+    let read = |register: Operand, b_set: bool, c_set: bool| match register {
+        Operand::B if !b_set => Err(ShapeError::CarriedRegister('B')),
+        Operand::C if !c_set => Err(ShapeError::CarriedRegister('C')),
+        _ => Ok(()),
+    };
+    for instruction in program.chunks(2) {
+        let combo = || Operand::decode(instruction[1]).expect("Checked by check_shape");
+        match Opcode::from(instruction[0]) {
+            Adv | Jnz => {}
+            Out => read(combo(), b_set, c_set)?,
+            Bxl => {
+                read(Operand::B, b_set, c_set)?;
+            }
+            Bxc => {
+                read(Operand::B, b_set, c_set)?;
+                read(Operand::C, b_set, c_set)?;
+            }
+            Bst | Bdv => {
+                read(combo(), b_set, c_set)?;
+                b_set = true;
+            }
+            Cdv => {
+                read(combo(), b_set, c_set)?;
+                c_set = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Output of a single pass through the loop, `None` if there is none or the pass fails.
+fn first_output(computer: &Computer, a: u64) -> Option<u8> {
+    let mut computer = computer.clone();
+    computer.registers.a = a;
+    while !computer.is_halted() {
+        if let Some(out) = computer.step().ok()? {
+            return Some(out);
+        }
+    }
+    None
+}
+
+/// Every initial `A` that makes the program output `target`, in ascending order.
+///
+/// The last pass sees only the highest 3 bits of `A`, so `A` is rebuilt from the last output
+/// value backwards, trying all 8 values of each next 3-bit group and backtracking when none
+/// fits. Candidates are confirmed by running the whole program.
+pub(super) fn solve_backward(computer: &Computer, target: &[u8]) -> Result<Vec<u64>, ShapeError> {
+    check_shape(&computer.instructions)?;
+
+    let program = DecodedProgram::from(computer);
+    let mut solutions = Vec::new();
+    let mut stack = vec![(target.len(), 0u64)];
+    while let Some((remaining, prefix)) = stack.pop() {
+        if remaining == 0 {
+            if program.outputs(prefix, target) {
+                solutions.push(prefix);
+            }
+            continue;
+        }
+        // A can't grow past 64 bits
+        if prefix >> 61 != 0 {
+            continue;
+        }
+        let expected = target[remaining - 1];
+        for bits in 0..8 {
+            let a = prefix << 3 | bits;
+            if first_output(computer, a) == Some(expected) {
+                stack.push((remaining - 1, a));
+            }
+        }
+    }
+
+    solutions.sort_unstable();
+    solutions.dedup();
+    Ok(solutions)
+}
+
+/// Every initial `A` that makes the program output itself, in ascending order.
+pub(super) fn solve_quine(computer: &Computer) -> Result<Vec<u64>, ShapeError> {
+    solve_backward(computer, &computer.instructions)
+}
+
+/// Lowest initial `A` that makes the program output itself.
+pub(super) fn lowest_quine(computer: &Computer) -> Result<u64, QuineError> {
+    let solutions = solve_quine(computer).map_err(QuineError::Shape)?;
+    solutions.first().copied().ok_or(QuineError::NoQuine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use super::*;
+
+    const SELF_RETURNING_PROGRAM: &str = r#"Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0"#;
+
+    #[test]
+    fn finds_all_quines() {
+        let solutions = solve_quine(&parse(SELF_RETURNING_PROGRAM)).unwrap();
+        assert_eq!(solutions.len(), 8);
+        assert_eq!(solutions[0], 117440);
+        assert!(solutions.iter().all(|a| a >> 3 == 117440 >> 3));
+    }
+
+    #[test]
+    fn rejects_other_shapes() {
+        let shape = |program: &[u8]| check_shape(program).err();
+        assert_eq!(shape(&[0, 3, 5, 4, 3]), Some(ShapeError::NotDecodable));
+        assert_eq!(shape(&[0, 3, 5, 7, 3, 0]), Some(ShapeError::NotDecodable));
+        assert_eq!(shape(&[0, 1, 5, 4, 3, 0]), Some(ShapeError::AdvNotThree(1)));
+        assert_eq!(shape(&[5, 4, 3, 0]), Some(ShapeError::AdvCount(0)));
+        assert_eq!(
+            shape(&[0, 3, 0, 3, 5, 4, 3, 0]),
+            Some(ShapeError::AdvCount(2))
+        );
+        assert_eq!(
+            shape(&[0, 3, 5, 4, 5, 4, 3, 0]),
+            Some(ShapeError::OutCount(2))
+        );
+        assert_eq!(shape(&[0, 3, 5, 4, 3, 2]), Some(ShapeError::NoLoop));
+        assert_eq!(shape(&[0, 3, 5, 4]), Some(ShapeError::NoLoop));
+        // bxl 1; out B; adv 3; jnz 0 keeps flipping B across passes
+        assert_eq!(
+            shape(&[1, 1, 5, 5, 0, 3, 3, 0]),
+            Some(ShapeError::CarriedRegister('B'))
+        );
+        assert_eq!(
+            shape(&[2, 4, 4, 0, 5, 5, 0, 3, 3, 0]),
+            Some(ShapeError::CarriedRegister('C'))
+        );
+        assert_eq!(shape(&[2, 4, 7, 5, 4, 0, 5, 5, 0, 3, 3, 0]), None);
+        assert_eq!(shape(&[2, 4, 1, 7, 5, 5, 0, 3, 3, 0]), None);
+
+        let mut carried = parse(SELF_RETURNING_PROGRAM);
+        carried.instructions = vec![1, 1, 5, 5, 0, 3, 3, 0];
+        assert_eq!(
+            solve_backward(&carried, &[1, 0]),
+            Err(ShapeError::CarriedRegister('B'))
+        );
+        assert_eq!(
+            ShapeError::AdvNotThree(1).to_string(),
+            "Expected adv 3, found operand 1"
+        );
+    }
+
+    #[test]
+    fn no_quine() {
+        // The last pass shifts A down to 0 and outputs 0 ^ 1, but the program ends with 0
+        let computer = Computer::assemble(
+            "loop:
+            adv 3
+            bst A
+            bxl 1
+            out B
+            jnz loop",
+        )
+        .unwrap();
+        assert_eq!(solve_quine(&computer), Ok(vec![]));
+        assert_eq!(lowest_quine(&computer), Err(QuineError::NoQuine));
+    }
+}
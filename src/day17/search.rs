@@ -0,0 +1,226 @@
+use super::quine::{check_shape, solve_backward};
+use super::{compute, Computer, DecodedProgram, Registers};
+use itertools::Itertools;
+use rayon::prelude::*;
+use std::fmt::{Display, Formatter};
+
+/// Which registers a search may change and how far it looks.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct SearchSpace {
+    /// Registers that are not varied keep the value of the loaded program.
+    pub vary_b: bool,
+    pub vary_c: bool,
+    /// Varied registers are tried in `0..bound`.
+    pub bound: u64,
+}
+
+impl SearchSpace {
+    /// Only `A`, below `bound`.
+    pub fn a_below(bound: u64) -> Self {
+        Self {
+            vary_b: false,
+            vary_c: false,
+            bound,
+        }
+    }
+}
+
+/// No registers in the search space produce the target output.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct NoSolution {
+    pub target: Vec<u8>,
+    pub space: SearchSpace,
+}
+
+impl Display for NoSolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let varied = ["A"]
+            .into_iter()
+            .chain(self.space.vary_b.then_some("B"))
+            .chain(self.space.vary_c.then_some("C"))
+            .join(", ");
+        write!(
+            f,
+            "No {} below {} outputs {}",
+            varied,
+            self.space.bound,
+            self.target.iter().join(",")
+        )
+    }
+}
+
+impl std::error::Error for NoSolution {}
+
+impl Computer {
+    /// Every register combination in `space` that makes the program output `target`, ordered by
+    /// `A`, then `B`, then `C`, so the first one is the minimal.
+    ///
+    /// Programs shaped like the puzzle's (see [`super::ShapeError`]) are solved backwards 3 bits
+    /// at a time when only `A` varies, anything else is tried value by value in parallel.
+    /// Candidates that fail or run past the step limit of [`Computer::set_limits`] don't match.
+    /// The search runs on a [`DecodedProgram`], and every match is run again on the interpreter,
+    /// so the result follows [`Computer`]'s semantics.
+    pub fn find_registers(
+        &self,
+        target: &[u8],
+        space: SearchSpace,
+    ) -> Result<Vec<Registers>, NoSolution> {
+        let fixed = self.registers;
+        let confirmed = |&registers: &Registers| {
+            let computer = Computer {
+                registers,
+                ..self.clone()
+            };
+            let mut output = Vec::new();
+            compute(registers.a, &computer, &mut output).is_ok_and(|o| o == target)
+        };
+        let backward = !space.vary_b && !space.vary_c && !target.is_empty();
+        let solutions: Vec<Registers> = if backward && check_shape(&self.instructions).is_ok() {
+            solve_backward(self, target)
+                .expect("Shape checked")
+                .into_iter()
+                .take_while(|&a| a < space.bound)
+                .map(|a| Registers { a, ..fixed })
+                .collect()
+        } else if !space.vary_b && !space.vary_c {
+            DecodedProgram::from(self)
+                .par_find(0..space.bound, target)
+                .into_iter()
+                .map(|a| Registers { a, ..fixed })
+                .collect()
+        } else {
+            let program = DecodedProgram::from(self);
+            let range = |vary: bool, value: u64| {
+                if vary {
+                    0..=space.bound.saturating_sub(1)
+                } else {
+                    value..=value
+                }
+            };
+            let (bs, cs) = (range(space.vary_b, fixed.b), range(space.vary_c, fixed.c));
+            (0..space.bound)
+                .into_par_iter()
+                .flat_map_iter(|a| {
+                    let cs = cs.clone();
+                    bs.clone()
+                        .flat_map(move |b| cs.clone().map(move |c| Registers { a, b, c }))
+                })
+                .filter(|&registers| program.outputs_registers(registers, target))
+                .collect()
+        };
+
+        let solutions: Vec<Registers> = solutions.into_iter().filter(confirmed).collect();
+        if solutions.is_empty() {
+            Err(NoSolution {
+                target: target.to_vec(),
+                space,
+            })
+        } else {
+            Ok(solutions)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Limits;
+    use super::*;
+
+    const EXAMPLE: &str = r#"Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0"#;
+
+    const SELF_RETURNING_PROGRAM: &str = r#"Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0"#;
+
+    #[test]
+    fn finds_minimal_a() {
+        // adv 1 doesn't fit the backward search, so this goes value by value
+        let computer = Computer::from_input(EXAMPLE).unwrap();
+        let found = computer
+            .find_registers(&[3, 1, 0], SearchSpace::a_below(100))
+            .unwrap();
+        assert_eq!(found.iter().map(|r| r.a).collect::<Vec<_>>(), vec![6, 7]);
+        assert!(found.iter().all(|r| r.b == 0 && r.c == 0));
+
+        let computer = Computer::from_input(SELF_RETURNING_PROGRAM).unwrap();
+        let found = computer
+            .find_registers(&[1, 2, 0], SearchSpace::a_below(u64::MAX))
+            .unwrap();
+        assert_eq!(found.len(), 8);
+        assert_eq!(found[0].a, 0o210);
+        let found = computer
+            .find_registers(&[1, 2, 0], SearchSpace::a_below(0o213))
+            .unwrap();
+        assert_eq!(found.len(), 3);
+    }
+
+    #[test]
+    fn varies_b_and_c() {
+        let computer = Computer::assemble("bxl 2\nbxc\nout B").unwrap();
+        let space = SearchSpace {
+            vary_b: true,
+            vary_c: true,
+            bound: 8,
+        };
+        let found = computer.find_registers(&[7], space).unwrap();
+        assert_eq!(found[0], Registers { a: 0, b: 0, c: 5 });
+        assert_eq!(found.len(), 8 * 8);
+    }
+
+    #[test]
+    fn reports_no_solution() {
+        let computer = Computer::from_input(SELF_RETURNING_PROGRAM).unwrap();
+        let error = computer
+            .find_registers(&[1, 2, 3], SearchSpace::a_below(1 << 20))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "No A below 1048576 outputs 1,2,3");
+        // never halts, so every candidate runs into the step limit
+        let mut computer = Computer::assemble("loop: jnz loop\nout A").unwrap();
+        computer.set_limits(Limits {
+            max_steps: 1000,
+            ..Limits::default()
+        });
+        assert!(computer
+            .find_registers(&[1], SearchSpace::a_below(4))
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_shapes_the_backward_search_gets_wrong() {
+        // Reads B and C before writing them, so only the value by value search is sound
+        let computer = Computer::from_input(
+            "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 1,1,5,5,0,3,3,0",
+        )
+        .unwrap();
+        let found = computer
+            .find_registers(&[1, 0], SearchSpace::a_below(1000))
+            .unwrap();
+        assert_eq!(found[0].a, 8);
+    }
+
+    #[test]
+    fn keeps_fixed_registers_at_the_maximum() {
+        let mut computer = Computer::assemble("bxc\nout B").unwrap();
+        computer.registers.b = u64::MAX;
+        let space = SearchSpace {
+            vary_b: false,
+            vary_c: true,
+            bound: 8,
+        };
+        let found = computer.find_registers(&[0], space).unwrap();
+        assert_eq!(
+            found[0],
+            Registers {
+                a: 0,
+                b: u64::MAX,
+                c: 7
+            }
+        );
+    }
+}
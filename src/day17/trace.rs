@@ -0,0 +1,259 @@
+use super::disasm::instruction;
+use super::{parse_computer, Computer, Registers, RunError};
+use crate::utils::parse::ParseError;
+use std::fmt::Write;
+
+/// One executed instruction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceStep {
+    pub pc: usize,
+    /// Mnemonic form, e.g. `bst A`.
+    pub instruction: String,
+    /// Operand after resolving combo operands.
+    pub argument: u64,
+    pub before: Registers,
+    pub after: Registers,
+    pub output: Option<u8>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Breakpoint {
+    /// Stop before the instruction at this address runs.
+    Pc(usize),
+    /// Stop once this many values have been output.
+    OutputCount(usize),
+}
+
+/// Why [`Debugger::run`] returned.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Stop {
+    Halted,
+    Breakpoint(Breakpoint),
+    StepLimit,
+    /// The instruction at `pc` couldn't run, it's not in the trace.
+    Error(RunError),
+}
+
+/// Runs a computer step by step, recording every instruction.
+pub struct Debugger {
+    computer: Computer,
+    breakpoints: Vec<Breakpoint>,
+    trace: Vec<TraceStep>,
+    outputs: usize,
+}
+
+impl Debugger {
+    pub(super) fn from_computer(computer: Computer) -> Self {
+        Self {
+            computer,
+            breakpoints: Vec::new(),
+            trace: Vec::new(),
+            outputs: 0,
+        }
+    }
+
+    /// Debugger for a program in puzzle input format.
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        parse_computer(input).map(Self::from_computer)
+    }
+
+    /// Replaces register `A` before running, like `part2_solution` does for each candidate.
+    pub fn set_a(&mut self, a: u64) {
+        self.computer.registers.a = a;
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    pub fn registers(&self) -> Registers {
+        self.computer.registers
+    }
+
+    pub fn pc(&self) -> usize {
+        self.computer.pc
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.computer.is_halted()
+    }
+
+    /// Runs one instruction, `None` if the computer has halted. A failing instruction leaves
+    /// the computer as it was.
+    pub fn step(&mut self) -> Result<Option<&TraceStep>, RunError> {
+        if self.computer.is_halted() {
+            return Ok(None);
+        }
+        let pc = self.computer.pc;
+        let (_, argument) = self.computer.decode()?;
+        let instruction = instruction(
+            self.computer.instructions[pc],
+            self.computer.instructions[pc + 1],
+        );
+        let before = self.computer.registers;
+        let output = self.computer.step()?;
+        if output.is_some() {
+            self.outputs += 1;
+        }
+        self.trace.push(TraceStep {
+            pc,
+            instruction,
+            argument,
+            before,
+            after: self.computer.registers,
+            output,
+        });
+        Ok(self.trace.last())
+    }
+
+    /// Runs until the computer halts, a breakpoint is hit or `max_steps` instructions ran.
+    /// The first instruction always runs, so calling `run` again continues past a breakpoint.
+    pub fn run(&mut self, max_steps: usize) -> Stop {
+        for i in 0..max_steps {
+            let at_pc = Breakpoint::Pc(self.computer.pc);
+            if i > 0 && self.breakpoints.contains(&at_pc) {
+                return Stop::Breakpoint(at_pc);
+            }
+            let output = match self.step() {
+                Ok(Some(step)) => step.output,
+                Ok(None) => return Stop::Halted,
+                Err(error) => return Stop::Error(error),
+            };
+            let after_output = Breakpoint::OutputCount(self.outputs);
+            if output.is_some() && self.breakpoints.contains(&after_output) {
+                return Stop::Breakpoint(after_output);
+            }
+        }
+        if self.computer.is_halted() {
+            Stop::Halted
+        } else {
+            Stop::StepLimit
+        }
+    }
+
+    pub fn trace(&self) -> &[TraceStep] {
+        &self.trace
+    }
+
+    pub fn output(&self) -> Vec<u8> {
+        self.trace.iter().filter_map(|s| s.output).collect()
+    }
+
+    /// Trace as an aligned text table, one row per step.
+    pub fn table(&self) -> String {
+        let mut table = format!(
+            "{:>5} {:>4}  {:<8} {:>16}  {:<50} {:<50} {}\n",
+            "step", "pc", "instr", "arg", "before A B C", "after A B C", "out"
+        );
+        for (i, step) in self.trace.iter().enumerate() {
+            let registers = |r: &Registers| format!("{:>16} {:>16} {:>16}", r.a, r.b, r.c);
+            let _ = writeln!(
+                table,
+                "{:>5} {:>4}  {:<8} {:>16}  {:<50} {:<50} {}",
+                i,
+                step.pc,
+                step.instruction,
+                step.argument,
+                registers(&step.before),
+                registers(&step.after),
+                step.output.map_or(String::new(), |o| o.to_string())
+            );
+        }
+        table
+    }
+
+    /// Trace as a JSON array of step objects.
+    pub fn to_json(&self) -> String {
+        let registers = |r: &Registers| format!(r#"{{"a":{},"b":{},"c":{}}}"#, r.a, r.b, r.c);
+        let steps: Vec<String> = self
+            .trace
+            .iter()
+            .map(|step| {
+                format!(
+                    r#"{{"pc":{},"instruction":"{}","argument":{},"before":{},"after":{},"output":{}}}"#,
+                    step.pc,
+                    step.instruction,
+                    step.argument,
+                    registers(&step.before),
+                    registers(&step.after),
+                    step.output.map_or("null".to_string(), |o| o.to_string())
+                )
+            })
+            .collect();
+        format!("[{}]", steps.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUINE: &str = "Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0";
+
+    #[test]
+    fn traces_steps() {
+        let mut debugger = Debugger::new(QUINE).unwrap();
+        debugger.set_a(117440);
+        assert_eq!(debugger.run(1000), Stop::Halted);
+        assert_eq!(debugger.output(), vec![0, 3, 5, 4, 3, 0]);
+        let first = &debugger.trace()[0];
+        assert_eq!((first.pc, first.instruction.as_str()), (0, "adv 3"));
+        assert_eq!(first.argument, 3);
+        assert_eq!(first.before.a, 117440);
+        assert_eq!(first.after.a, 117440 / 8);
+        assert_eq!(debugger.trace()[1].output, Some(0));
+        assert_eq!(debugger.step(), Ok(None));
+    }
+
+    #[test]
+    fn stops_on_errors() {
+        let mut debugger = Debugger::new("Register A: 0\nProgram: 5,4,5,7").unwrap();
+        let error = RunError::InvalidOperand { pc: 2, operand: 7 };
+        assert_eq!(debugger.run(1000), Stop::Error(error));
+        assert_eq!(debugger.output(), vec![0]);
+        assert_eq!(debugger.pc(), 2);
+        assert_eq!(debugger.step(), Err(error));
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut debugger = Debugger::new(QUINE).unwrap();
+        debugger.add_breakpoint(Breakpoint::Pc(4));
+        assert_eq!(debugger.run(1000), Stop::Breakpoint(Breakpoint::Pc(4)));
+        assert_eq!(debugger.pc(), 4);
+        assert_eq!(debugger.trace().len(), 2);
+        assert_eq!(debugger.run(1000), Stop::Breakpoint(Breakpoint::Pc(4)));
+        assert_eq!(debugger.trace().len(), 5);
+
+        let mut debugger = Debugger::new(QUINE).unwrap();
+        debugger.add_breakpoint(Breakpoint::OutputCount(2));
+        assert_eq!(
+            debugger.run(1000),
+            Stop::Breakpoint(Breakpoint::OutputCount(2))
+        );
+        assert_eq!(debugger.output(), vec![5, 7]);
+        assert_eq!(debugger.run(2), Stop::StepLimit);
+    }
+
+    #[test]
+    fn exports() {
+        let mut debugger = Debugger::new(QUINE).unwrap();
+        debugger.run(2);
+        assert_eq!(
+            debugger.to_json(),
+            concat!(
+                r#"[{"pc":0,"instruction":"adv 3","argument":3,"before":{"a":2024,"b":0,"c":0},"#,
+                r#""after":{"a":253,"b":0,"c":0},"output":null},"#,
+                r#"{"pc":2,"instruction":"out A","argument":253,"before":{"a":253,"b":0,"c":0},"#,
+                r#""after":{"a":253,"b":0,"c":0},"output":5}]"#
+            )
+        );
+        let table = debugger.table();
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.lines().nth(2).unwrap().trim_end().ends_with(" 5"));
+    }
+}
@@ -1,9 +1,8 @@
-use crate::utils::binary_find;
-use crate::utils::c2::{C2Field, C2};
+use crate::utils::bitgrid::BitGrid;
+use crate::utils::c2::C2;
+use crate::utils::parse::{lines, pair};
+use crate::utils::search::{first_true, par_first_true};
 use aoc_runner_derive::{aoc, aoc_generator};
-use binary_heap_plus::BinaryHeap;
-use prse::parse;
-use std::cmp::Ordering;
 
 // CodSpeed compatibility
 #[allow(dead_code, clippy::useless_format)]
@@ -18,79 +17,20 @@ pub fn part2(input: &str) -> String {
 
 #[aoc_generator(day18)]
 fn parse(input: &str) -> Vec<C2> {
-    input
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|l| {
-            let (x, y): (i32, i32) = parse!(l, "{},{}");
-            C2::new(x, y)
-        })
-        .collect()
-}
-
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-struct TravelEntry {
-    coord: C2,
-    moves: u16,
-}
-
-impl Ord for TravelEntry {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let moves_cmp = (!self.moves).cmp(&(!other.moves));
-
-        if moves_cmp == Ordering::Equal {
-            let a = self.coord.x + self.coord.y;
-            let b = other.coord.x + other.coord.y;
-            a.cmp(&b)
-        } else {
-            moves_cmp
-        }
-    }
-}
-
-impl TravelEntry {
-    fn new(coord: C2, moves: u16) -> Self {
-        Self { coord, moves }
-    }
-}
-
-impl PartialOrd for TravelEntry {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+    lines(input, |line| pair(line, ",").map(|(x, y)| C2::new(x, y)))
+        .unwrap_or_else(|e| panic!("Bad input at {e}"))
 }
 
 fn find_min_moves(obstacles: &[C2], obstacle_limit: usize, size: usize) -> u16 {
     let finish = C2::new((size - 1) as i32, (size - 1) as i32);
-    let mut field: C2Field<u16> = C2Field::new(size, size);
+    let mut field = BitGrid::full(size, size);
     for c in obstacles.iter().take(obstacle_limit) {
-        field.set(c, u16::MAX);
+        field.set(c, false);
     }
 
-    let mut to_visit: BinaryHeap<TravelEntry> = BinaryHeap::new();
-    to_visit.push(TravelEntry::new(C2::new(0, 0), 0));
-
-    while let Some(t) = to_visit.pop() {
-        let c = t.coord;
-        if let Some(moves) = field.get(&c) {
-            if *moves == 0 || t.moves <= *moves {
-                let moves = moves + 1;
-                for n in c.neighbors_4() {
-                    if n == finish {
-                        return moves;
-                    }
-                    if let Some(n_moves) = field.get(&n) {
-                        if *n_moves == 0 || (*n_moves != u16::MAX && *n_moves > moves) {
-                            field.set(&n, moves);
-                            to_visit.push(TravelEntry::new(n, moves));
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    u16::MAX
+    field
+        .distance(&C2::ZERO, &finish)
+        .map_or(u16::MAX, |moves| moves as u16)
 }
 
 #[aoc(day18, part1)]
@@ -99,10 +39,16 @@ fn part1_solution(input: &[C2]) -> u16 {
 }
 
 fn find_blocking(obstacles: &[C2], limit: usize, size: usize) -> String {
-    let low = limit;
-    let high = obstacles.len();
-    let will_be_stuck = |limit: usize| find_min_moves(obstacles, limit + 1, size) != u16::MAX;
-    let block = binary_find(low, high, will_be_stuck);
+    let is_blocked = |limit: usize| find_min_moves(obstacles, limit + 1, size) == u16::MAX;
+    let block = first_true(limit, obstacles.len(), is_blocked).expect("Never blocked");
+    let block_coord = obstacles[block];
+    format!("{},{}", block_coord.x, block_coord.y)
+}
+
+fn find_blocking_parallel(obstacles: &[C2], limit: usize, size: usize) -> String {
+    let is_blocked = |limit: usize| find_min_moves(obstacles, limit + 1, size) == u16::MAX;
+    let ways = rayon::current_num_threads().max(2);
+    let block = par_first_true(limit, obstacles.len(), ways, is_blocked).expect("Never blocked");
     let block_coord = obstacles[block];
     format!("{},{}", block_coord.x, block_coord.y)
 }
@@ -112,6 +58,11 @@ fn part2_solution(input: &[C2]) -> String {
     find_blocking(input, 1024, 71)
 }
 
+#[aoc(day18, part2, Parallel)]
+fn part2_solution_parallel(input: &[C2]) -> String {
+    find_blocking_parallel(input, 1024, 71)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,5 +99,6 @@ mod tests {
     #[test]
     fn part2_example() {
         assert_eq!(find_blocking(&parse(SMALL_EXAMPLE), 12, 7), "6,1");
+        assert_eq!(find_blocking_parallel(&parse(SMALL_EXAMPLE), 12, 7), "6,1");
     }
 }
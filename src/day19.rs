@@ -1,5 +1,5 @@
+use crate::utils::memo::{Memo, SharedMemo};
 use aoc_runner_derive::{aoc, aoc_generator};
-use fxhash::FxHashMap;
 use rayon::iter::ParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
 
@@ -36,40 +36,29 @@ fn parse(input: &str) -> Input {
     (patterns, towels)
 }
 
-fn check_if_possible(
-    towel: String,
-    patterns: &Vec<String>,
-    cache: &mut FxHashMap<String, bool>,
+fn check_if_possible<'a>(
+    towel: &'a str,
+    patterns: &[String],
+    memo: &mut Memo<&'a str, bool>,
 ) -> bool {
     if towel.is_empty() {
         return true;
     }
-    if let Some(result) = cache.get(&towel) {
-        return *result;
-    }
-
-    for pattern in patterns {
-        if towel.len() >= pattern.len() {
-            let towel_part = &towel[0..pattern.len()];
-            let pattern = pattern.as_str();
-            if towel_part == pattern
-                && check_if_possible(towel[pattern.len()..].to_string(), patterns, cache)
-            {
-                cache.insert(towel, true);
-                return true;
-            }
-        }
-    }
-    cache.insert(towel, false);
 
-    false
+    memo.compute(towel, |memo, _| {
+        patterns.iter().any(|pattern| {
+            towel
+                .strip_prefix(pattern.as_str())
+                .is_some_and(|rest| check_if_possible(rest, patterns, memo))
+        })
+    })
 }
 #[aoc(day19, part1)]
 fn part1_solution(input: &Input) -> usize {
     input
         .1
         .par_iter()
-        .filter(|towel| check_if_possible((*towel).clone(), &input.0, &mut FxHashMap::default()))
+        .filter(|towel| check_if_possible(towel, &input.0, &mut Memo::new()))
         .count()
 }
 
@@ -78,52 +67,53 @@ fn part1_solution_using_part2(input: &Input) -> usize {
     input
         .1
         .par_iter()
-        .filter(|towel| {
-            check_possible_ways((*towel).clone(), &input.0, &mut FxHashMap::default()) > 0
-        })
+        .filter(|towel| check_possible_ways(towel, &input.0, &mut Memo::new()) > 0)
         .count()
 }
 #[aoc(day19, part1, SharedCache)]
 fn part1_solution_shared(input: &Input) -> usize {
-    let mut cache = FxHashMap::default();
+    let mut memo = Memo::new();
     input
         .1
         .iter()
-        .filter(|towel| check_if_possible((*towel).clone(), &input.0, &mut cache))
+        .filter(|towel| check_if_possible(towel, &input.0, &mut memo))
         .count()
 }
 
-fn check_possible_ways(
-    towel: String,
-    patterns: &Vec<String>,
-    cache: &mut FxHashMap<String, usize>,
+fn check_possible_ways<'a>(
+    towel: &'a str,
+    patterns: &[String],
+    memo: &mut Memo<&'a str, usize>,
 ) -> usize {
     if towel.is_empty() {
         return 1;
     }
-    if let Some(result) = cache.get(&towel) {
-        return *result;
-    }
 
-    for pattern in patterns {
-        if towel.len() >= pattern.len() {
-            let towel_part = &towel[0..pattern.len()];
-            let pattern = pattern.as_str();
-            if towel_part == pattern {
-                let ways = check_possible_ways(towel[pattern.len()..].to_string(), patterns, cache);
-                cache
-                    .entry(towel.clone())
-                    .and_modify(|e| *e += ways)
-                    .or_insert(ways);
-            }
-        }
-    }
+    memo.compute(towel, |memo, _| {
+        patterns
+            .iter()
+            .filter_map(|pattern| towel.strip_prefix(pattern.as_str()))
+            .map(|rest| check_possible_ways(rest, patterns, memo))
+            .sum()
+    })
+}
 
-    if let Some(result) = cache.get(&towel) {
-        *result
-    } else {
-        0
+fn check_possible_ways_shared<'a>(
+    towel: &'a str,
+    patterns: &[String],
+    memo: &SharedMemo<&'a str, usize>,
+) -> usize {
+    if towel.is_empty() {
+        return 1;
     }
+
+    memo.compute(towel, |_| {
+        patterns
+            .iter()
+            .filter_map(|pattern| towel.strip_prefix(pattern.as_str()))
+            .map(|rest| check_possible_ways_shared(rest, patterns, memo))
+            .sum()
+    })
 }
 
 #[aoc(day19, part2)]
@@ -131,17 +121,27 @@ fn part2_solution(input: &Input) -> usize {
     input
         .1
         .par_iter()
-        .map(|towel| check_possible_ways(towel.clone(), &input.0, &mut FxHashMap::default()))
+        .map(|towel| check_possible_ways(towel, &input.0, &mut Memo::new()))
         .sum()
 }
 
 #[aoc(day19, part2, SharedCache)]
 fn part2_solution_shared(input: &Input) -> usize {
-    let mut cache = FxHashMap::default();
+    let mut memo = Memo::new();
     input
         .1
         .iter()
-        .map(|towel| check_possible_ways(towel.clone(), &input.0, &mut cache))
+        .map(|towel| check_possible_ways(towel, &input.0, &mut memo))
+        .sum()
+}
+
+#[aoc(day19, part2, SharedParallel)]
+fn part2_solution_shared_parallel(input: &Input) -> usize {
+    let memo = SharedMemo::new();
+    input
+        .1
+        .par_iter()
+        .map(|towel| check_possible_ways_shared(towel, &input.0, &memo))
         .sum()
 }
 
@@ -181,4 +181,9 @@ bbrgwb"#;
     fn part2_example_shared() {
         assert_eq!(part2_solution_shared(&parse(EXAMPLE)), 16);
     }
+
+    #[test]
+    fn part2_example_shared_parallel() {
+        assert_eq!(part2_solution_shared_parallel(&parse(EXAMPLE)), 16);
+    }
 }
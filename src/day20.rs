@@ -1,12 +1,11 @@
-use crate::utils::c2::{C2Field, C2};
-use crate::utils::maze::{parse_maze, Maze};
+use crate::utils::bitgrid::BitGrid;
+use crate::utils::c2::C2;
+use crate::utils::maze::{Maze, MazeSpec};
 use aoc_runner_derive::{aoc, aoc_generator};
-use binary_heap_plus::BinaryHeap;
 use fxhash::FxHashMap;
 use rayon::iter::IndexedParallelIterator;
 use rayon::iter::ParallelIterator;
 use rayon::prelude::*;
-use std::io::Write;
 
 // CodSpeed compatibility
 #[allow(dead_code, clippy::useless_format)]
@@ -21,36 +20,26 @@ pub fn part2(input: &str) -> String {
 
 #[aoc_generator(day20)]
 fn parse(input: &str) -> Maze {
-    parse_maze(input)
+    MazeSpec::start_end()
+        .parse(input)
+        .unwrap_or_else(|e| panic!("Bad input: {e}"))
+        .into_start_end()
 }
 
-fn route(field: &C2Field<bool>, start: C2, end: C2) -> Vec<C2> {
-    let mut moves_to_check = BinaryHeap::new_by_key(|(c, _)| *c);
-    moves_to_check.push((0, start));
+/// Cells of a shortest path from `start` to `end`, found by expanding whole BFS layers.
+fn route(field: &BitGrid, start: C2, end: C2) -> Vec<C2> {
     let mut prices: FxHashMap<C2, u32> = FxHashMap::default();
     prices.insert(start, 0);
-    while let Some((p, m)) = moves_to_check.pop() {
-        std::io::stdout().flush().unwrap();
-        if m == end {
-            break;
-        }
-        if let Some(price) = prices.get(&m) {
-            if *price < p {
-                continue;
-            }
-        }
-        let price = p + 1;
-        for next in m.neighbors_4() {
-            if *field.get(&next).unwrap_or(&false) {
-                if let Some(next_price) = prices.get(&next) {
-                    if *next_price < price {
-                        continue;
-                    }
-                }
-                moves_to_check.push((price, next));
-                prices.insert(next, price);
-            }
-        }
+    let mut visited = BitGrid::new(field.width(), field.height());
+    visited.set(&start, true);
+    let mut frontier = visited.clone();
+    let mut price = 0;
+    while !frontier.get(&end) {
+        frontier = frontier.expand4().intersection(field).difference(&visited);
+        assert!(!frontier.is_empty(), "End can't be reached");
+        visited = visited.union(&frontier);
+        price += 1;
+        prices.extend(frontier.iter().map(|c| (c, price)));
     }
 
     let mut backtrace: Vec<C2> = vec![end];
@@ -1,11 +1,11 @@
-use crate::utils::c2::{C2, C2_DOWN, C2_LEFT, C2_RIGHT, C2_UP};
+use crate::utils::c2::{Direction, C2};
+use crate::utils::memo::Memo;
 use aoc_runner_derive::{aoc, aoc_generator};
-use cached::once_cell::sync::Lazy;
-use cached::proc_macro::cached;
 use fxhash::{FxHashMap, FxHashSet};
 use itertools::Itertools;
 use std::collections::VecDeque;
 use std::string::ToString;
+use std::sync::LazyLock;
 
 // CodSpeed compatibility
 #[allow(dead_code, clippy::useless_format)]
@@ -34,9 +34,9 @@ const KEYPAD_BUTTONS: [(C2, char); 11] = [
     (C2::new(2, 3), 'A'),
 ];
 
-static KEYPAD_TRANSITIONS: Lazy<FxHashMap<(char, char), Vec<String>>> =
-    Lazy::new(|| calculate_all_transitions(KEYPAD_BUTTONS.as_slice()));
-static ROBOT_TRANSITIONS: Lazy<FxHashMap<(char, char), String>> = Lazy::new(|| {
+static KEYPAD_TRANSITIONS: LazyLock<FxHashMap<(char, char), Vec<String>>> =
+    LazyLock::new(|| calculate_all_transitions(KEYPAD_BUTTONS.as_slice()));
+static ROBOT_TRANSITIONS: LazyLock<FxHashMap<(char, char), String>> = LazyLock::new(|| {
     FxHashMap::from_iter(vec![
         (('^', '^'), "A".to_string()),
         (('^', 'A'), ">A".to_string()),
@@ -80,12 +80,6 @@ fn find_shortest_paths(a: C2, b: C2, allowed: &FxHashSet<C2>) -> Vec<String> {
         return vec!["A".to_string()];
     }
 
-    let directions = vec![
-        ('^', C2_UP),
-        ('>', C2_RIGHT),
-        ('v', C2_DOWN),
-        ('<', C2_LEFT),
-    ];
     let mut results = Vec::new();
     let mut queue = VecDeque::new();
 
@@ -100,8 +94,9 @@ fn find_shortest_paths(a: C2, b: C2, allowed: &FxHashSet<C2>) -> Vec<String> {
             }
         }
 
-        for &(dir, cdir) in &directions {
-            let next = current + cdir;
+        for direction in Direction::iter4() {
+            let dir = direction.to_arrow().expect("Cardinal direction");
+            let next = current + direction;
             if allowed.contains(&next) && !visited.contains(&next) {
                 let mut new_path = path.clone();
                 new_path.push(dir);
@@ -171,7 +166,9 @@ fn get_numeric(vec: &str) -> u64 {
     number as u64
 }
 
-fn calculate_cost(data: &String, depth: u8) -> u64 {
+type CostMemo = Memo<(char, char, u8), u64>;
+
+fn calculate_cost(data: &String, depth: u8, memo: &mut CostMemo) -> u64 {
     let full_move = format!("A{data}");
 
     let result = full_move
@@ -181,39 +178,39 @@ fn calculate_cost(data: &String, depth: u8) -> u64 {
         .map(|w| {
             let b = w[0];
             let c = w[1];
-            calculate_robot_cost(b, c, depth - 1)
+            calculate_robot_cost(b, c, depth - 1, memo)
         })
         .sum::<u64>();
     result
 }
 
-#[cached]
-fn calculate_robot_cost(b: char, c: char, depth: u8) -> u64 {
-    let forward = ROBOT_TRANSITIONS.get(&(b, c)).expect("forward");
-    let result = if depth == 0 {
-        forward.len() as u64
-    } else {
-        let full_move = format!("A{forward}").chars().collect_vec();
+fn calculate_robot_cost(b: char, c: char, depth: u8, memo: &mut CostMemo) -> u64 {
+    memo.compute((b, c, depth), |memo, _| {
+        let forward = ROBOT_TRANSITIONS.get(&(b, c)).expect("forward");
+        if depth == 0 {
+            forward.len() as u64
+        } else {
+            let full_move = format!("A{forward}").chars().collect_vec();
 
-        let mut sum = 0;
+            let mut sum = 0;
 
-        for i in 0..full_move.len() - 1 {
-            let b = full_move[i];
-            let c = full_move[i + 1];
-            sum += calculate_robot_cost(b, c, depth - 1);
+            for i in 0..full_move.len() - 1 {
+                let b = full_move[i];
+                let c = full_move[i + 1];
+                sum += calculate_robot_cost(b, c, depth - 1, memo);
+            }
+            sum
         }
-        sum
-    };
-
-    result
+    })
 }
 
 fn solve(input: &Input, depth: u8) -> u64 {
+    let mut memo = CostMemo::new();
     let mut result = 0;
     for s in input.iter() {
         let min_path = process_keypad_transitions(s.clone())
             .iter()
-            .map(|comb| calculate_cost(comb, depth))
+            .map(|comb| calculate_cost(comb, depth, &mut memo))
             .min()
             .expect("combination");
         result += min_path * get_numeric(s);
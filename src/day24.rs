@@ -1,15 +1,25 @@
 use crate::day24::Key::{O, X, Y, Z};
 use crate::day24::Op::{And, Or, Xor};
+use crate::utils::parse::{
+    blocks, column_of, integers_n, key_value, lines, ParseError, ParseResult,
+};
 use aoc_runner_derive::{aoc, aoc_generator};
 use fxhash::{FxHashMap, FxHashSet};
 use itertools::Itertools;
-use prse::try_parse;
 use rayon::prelude::{ParallelBridge, ParallelIterator};
 use std::cmp::{max, Ordering};
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
+use std::str::FromStr;
 use std::string::ToString;
 
+mod export;
+mod netlist;
+mod repair;
+
+pub use export::{export, ExportError, Format};
+use netlist::Netlist;
+
 // CodSpeed compatibility
 #[allow(dead_code, clippy::useless_format)]
 pub fn part1(input: &str) -> String {
@@ -108,6 +118,16 @@ enum Op {
     Xor,
 }
 
+impl Display for Op {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Or => write!(f, "OR"),
+            And => write!(f, "AND"),
+            Xor => write!(f, "XOR"),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 enum OperationClassification {
     Suspicious,
@@ -230,15 +250,22 @@ impl Operation {
     }
 }
 
-impl From<&str> for Key {
-    fn from(value: &str) -> Self {
-        let start = &value[0..1];
-        let rest = &value[1..];
-        match start {
-            "x" => X(rest.parse::<usize>().expect("x")),
-            "y" => Y(rest.parse::<usize>().expect("y")),
-            "z" => Z(rest.parse::<usize>().expect("z")),
-            _ => O(value.to_string()),
+impl FromStr for Key {
+    type Err = String;
+
+    /// `x`, `y` and `z` followed by a bit number, or any other non-empty name.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut chars = value.chars();
+        let bit = |rest: &str| {
+            rest.parse::<usize>()
+                .map_err(|_| format!("Bad bit number in wire '{value}'"))
+        };
+        match chars.next() {
+            None => Err("Empty wire name".to_string()),
+            Some('x') => bit(chars.as_str()).map(X),
+            Some('y') => bit(chars.as_str()).map(Y),
+            Some('z') => bit(chars.as_str()).map(Z),
+            Some(_) => Ok(O(value.to_string())),
         }
     }
 }
@@ -252,143 +279,102 @@ struct Input {
 }
 
 impl Input {
-    pub fn set_input(&mut self, x: u64, y: u64) {
-        self.x = x;
-        self.y = y;
-    }
-
+    /// Exchanges the outputs of gate pairs. Pairs with a wire no gate drives are skipped.
     pub fn with_swaps(self, swaps: &[(Key, Key)]) -> Self {
         let mut operations = self.operations.clone();
 
         for (k1, k2) in swaps.iter().cloned() {
-            if let (Some(val1), Some(val2)) = (operations.remove(&k1), operations.remove(&k2)) {
-                operations.insert(k1, val2);
-                operations.insert(k2, val1);
+            if !operations.contains_key(&k1) || !operations.contains_key(&k2) {
+                continue;
             }
+            let mut val1 = operations.remove(&k1).expect("Checked");
+            let mut val2 = operations.remove(&k2).expect("Checked");
+            val1.target = k2.clone();
+            val2.target = k1.clone();
+            operations.insert(k1, val2);
+            operations.insert(k2, val1);
         }
         Self { operations, ..self }
     }
-
-    pub fn expected_z(&self) -> u64 {
-        self.x + self.y
-    }
 }
 
-#[aoc_generator(day24)]
-fn parse(input: &str) -> Input {
+fn parse_input(input: &str) -> ParseResult<Input> {
     let mut operations: FxHashMap<Key, Operation> = FxHashMap::default();
     let mut x = 0u64;
     let mut y = 0u64;
     let mut z_size = 0usize;
-    for line in input.lines() {
-        if line.len() == 6 {
-            let letter = &line[0..1];
-            let index = &line[1..3].parse::<usize>().expect("Couldn't parse index");
-            let value = line[5..].parse::<u64>().expect("Couldn't parse value");
-
-            match letter {
-                "x" => x += value << index,
-                "y" => y += value << index,
-                _ => panic!("Unknown letter {letter}"),
-            }
-        } else {
-            let expression_result: Result<(&str, &str, &str, &str), _> =
-                try_parse!(line, "{} {} {} -> {}");
-            if let Ok((op1, operation, op2, key)) = expression_result {
-                if let Some(number) = key.strip_prefix("z") {
-                    z_size = max(
-                        z_size,
-                        number.parse::<usize>().expect("Couldn't parse z_size"),
-                    );
-                }
-                let k1 = op1.into();
-                let k2 = op2.into();
-                let op = match operation {
-                    "OR" => Or,
-                    "AND" => And,
-                    "XOR" => Xor,
-                    _ => panic!("Unknow operation {operation}"),
-                };
-                operations.insert(key.into(), Operation::new(k1, k2, op, key.into()));
-            }
+    let blocks = blocks(input);
+    let (wires_line, wires) = blocks.first().copied().unwrap_or((1, ""));
+    let (gates_line, gates) = blocks.get(1).copied().unwrap_or((1, ""));
+
+    lines(wires, |line| {
+        let (wire, value) = key_value(line, ":")?;
+        let [value]: [u64; 1] = integers_n(value)?;
+        let error = |message: String| ParseError::new(1, column_of(line, wire), message);
+        let key = wire.parse::<Key>().map_err(error)?;
+        let (register, index) = match key {
+            X(index) => (&mut x, index),
+            Y(index) => (&mut y, index),
+            _ => return Err(error(format!("Unknown input wire '{wire}'"))),
+        };
+        if index >= 64 {
+            return Err(error(format!("Wire '{wire}' doesn't fit in 64 bits")));
         }
-    }
-    Input {
+        *register += value << index;
+        Ok(())
+    })
+    .map_err(|e| e.on_line(wires_line))?;
+
+    lines(gates, |line| {
+        let (expression, key) = key_value(line, "->")?;
+        let Some((op1, operation, op2)) = expression.split_whitespace().collect_tuple() else {
+            return Err(ParseError::new(1, 1, "Expected '<wire> <op> <wire>'"));
+        };
+        let wire = |part: &str| {
+            part.parse::<Key>()
+                .map_err(|message| ParseError::new(1, column_of(line, part), message))
+        };
+        let (k1, k2, target) = (wire(op1)?, wire(op2)?, wire(key)?);
+        if let Z(number) = target {
+            z_size = max(z_size, number);
+        }
+        let op = match operation {
+            "OR" => Or,
+            "AND" => And,
+            "XOR" => Xor,
+            _ => {
+                return Err(ParseError::new(
+                    1,
+                    column_of(line, operation),
+                    format!("Unknown operation '{operation}'"),
+                ))
+            }
+        };
+        operations.insert(target.clone(), Operation::new(k1, k2, op, target));
+        Ok(())
+    })
+    .map_err(|e| e.on_line(gates_line))?;
+
+    Ok(Input {
         x,
         y,
         z_last_index: z_size,
         operations,
-    }
-}
-
-fn find_value(key: &Key, data: &Input) -> bool {
-    match key {
-        X(i) => (data.x & (1u64 << i)) > 0,
-        Y(i) => (data.y & (1u64 << i)) > 0,
-        _ => {
-            let operation = &data.operations[key];
-            let k1 = &operation.k1;
-            let k2 = &operation.k2;
-            match operation.op {
-                Or => find_value(k1, data) || find_value(k2, data),
-                And => find_value(k1, data) && find_value(k2, data),
-                Xor => find_value(k1, data) ^ find_value(k2, data),
-            }
-        }
-    }
+    })
 }
 
-fn solve(data: &Input) -> u64 {
-    let mut z = 0;
-    for i in 0..=data.z_last_index {
-        if find_value(&Z(i), data) {
-            z += 1 << i;
-        }
-    }
-    z
+#[aoc_generator(day24)]
+fn parse(input: &str) -> Input {
+    parse_input(input).unwrap_or_else(|e| panic!("Bad input at {e}"))
 }
 
 #[aoc(day24, part1)]
 fn part1_solution(input: &Input) -> u64 {
-    solve(input)
+    Netlist::compile(input)
+        .unwrap_or_else(|e| panic!("Bad circuit: {e}"))
+        .evaluate(input.x, input.y)
 }
 
-fn is_ok(input: &Input) -> bool {
-    input.expected_z() == solve(input)
-}
-
-fn check_bit(bit: usize, input: &Input) -> bool {
-    let value = 1u64 << bit;
-    let mut input = input.clone();
-    input.set_input(value, value);
-    if !is_ok(&input) {
-        return false;
-    }
-    input.set_input(value, 0);
-    if !is_ok(&input) {
-        return false;
-    }
-    input.set_input(0, value);
-    if !is_ok(&input) {
-        return false;
-    }
-
-    true
-}
-
-fn has_cycle(key: &Key, input: &Input, seen: &mut FxHashSet<Key>) -> bool {
-    if seen.insert(key.clone()) {
-        if let Some(op) = input.operations.get(key) {
-            if has_cycle(&op.k1, input, seen) || has_cycle(&op.k2, input, seen) {
-                return true;
-            }
-        }
-        seen.remove(key);
-        false
-    } else {
-        true
-    }
-}
 fn verify(swaps: &[(Key, Key)], input: &Input) -> bool {
     let mut parity_check: FxHashSet<Key> = Default::default();
     for (a, b) in swaps.iter().cloned() {
@@ -401,21 +387,7 @@ fn verify(swaps: &[(Key, Key)], input: &Input) -> bool {
     }
 
     let input = input.clone().with_swaps(swaps);
-
-    if parity_check
-        .iter()
-        .any(|k| has_cycle(k, &input, &mut FxHashSet::default()))
-    {
-        return false;
-    }
-
-    for i in 0..input.z_last_index {
-        if !check_bit(i, &input) {
-            return false;
-        }
-    }
-
-    true
+    Netlist::compile(&input).is_ok_and(|netlist| netlist.is_adder(input.z_last_index))
 }
 
 fn backtrace_suspicious_node(op: &Operation, input: &Input, errors: &mut FxHashSet<Key>) {
@@ -455,6 +427,16 @@ fn find_suspicious_nodes(input: &Input) -> FxHashSet<Key> {
 }
 #[aoc(day24, part2)]
 fn part2_solution(input: &Input) -> String {
+    repair::repair(input)
+        .unwrap_or_else(|e| panic!("Can't repair the adder: {e}"))
+        .into_iter()
+        .flat_map(|swap| [swap.a, swap.b])
+        .sorted()
+        .join(",")
+}
+
+#[aoc(day24, part2, BruteForce)]
+fn part2_solution_brute_force(input: &Input) -> String {
     let mut potential_swaps = find_suspicious_nodes(input);
 
     potential_swaps.retain(|k| !k.is_input());
@@ -545,4 +527,38 @@ tnw OR pbm -> gnj"#;
     fn part1_example() {
         assert_eq!(part1_solution(&parse(EXAMPLE)), 2024);
     }
+
+    #[test]
+    fn reports_bad_wires() {
+        let error = |input: &str| parse_input(input).err().map(|e| e.to_string());
+        assert_eq!(
+            error(": 1\n\nx00 AND y00 -> z00"),
+            Some("1:1: Empty wire name".to_string())
+        );
+        assert_eq!(
+            error("x00: 1\n\nx00 AND yab -> z00"),
+            Some("3:9: Bad bit number in wire 'yab'".to_string())
+        );
+        assert_eq!(
+            error("x64: 1\n\nx00 AND y00 -> z00"),
+            Some("1:1: Wire 'x64' doesn't fit in 64 bits".to_string())
+        );
+        assert_eq!(
+            error("x00: 1\n\n a  FOO b -> z00"),
+            Some("3:5: Unknown operation 'FOO'".to_string())
+        );
+    }
+
+    #[test]
+    fn part2_swapped_adder() {
+        let swaps = [
+            ("z03", "a03"),
+            ("s05", "a05"),
+            ("c06", "z07"),
+            ("a09", "z10"),
+        ]
+        .map(|(a, b)| (a.parse::<Key>().unwrap(), b.parse::<Key>().unwrap()));
+        let input = parse(&netlist::tests::ripple_carry_adder(12)).with_swaps(&swaps);
+        assert_eq!(part2_solution(&input), "a03,a05,a09,c06,s05,z03,z07,z10");
+    }
 }
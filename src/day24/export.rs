@@ -0,0 +1,321 @@
+use super::netlist::{Netlist, NetlistError};
+use super::Key::{O, X, Y, Z};
+use super::{find_suspicious_nodes, parse_input, Input, Key, Op, OperationClassification};
+use crate::utils::parse::ParseError;
+use fxhash::{FxHashMap, FxHashSet};
+use itertools::Itertools;
+use std::fmt::{Display, Formatter, Write};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Graphviz graph, gates colored by their role in the adder.
+    Dot,
+    /// Structural Verilog module with one `assign` per gate.
+    Verilog,
+    /// Gates with their inputs, role and whether they look wrong.
+    Json,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExportError {
+    Parse(ParseError),
+    /// A swapped wire isn't the output of any gate.
+    NotAGate(String),
+    /// After the swaps, this wire depends on itself.
+    Cycle(String),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Parse(e) => write!(f, "Bad input at {e}"),
+            ExportError::NotAGate(wire) => write!(f, "No gate drives '{wire}'"),
+            ExportError::Cycle(wire) => write!(f, "Wire {wire} depends on itself"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Exports the circuit of a puzzle input after exchanging the outputs of each pair in `swaps`.
+pub fn export(input: &str, format: Format, swaps: &[(&str, &str)]) -> Result<String, ExportError> {
+    let input = parse_input(input).map_err(ExportError::Parse)?;
+    let mut keys = Vec::with_capacity(swaps.len());
+    for &(a, b) in swaps {
+        let key = |wire: &str| match wire.parse::<Key>() {
+            Ok(key) if input.operations.contains_key(&key) => Ok(key),
+            _ => Err(ExportError::NotAGate(wire.to_string())),
+        };
+        keys.push((key(a)?, key(b)?));
+    }
+    let input = input.with_swaps(&keys);
+    // Classifying follows gate inputs and would never end on a loop
+    if let Err(NetlistError::Cycle(key)) = Netlist::compile(&input) {
+        return Err(ExportError::Cycle(key.to_string()));
+    }
+    Ok(match format {
+        Format::Dot => input.to_dot(),
+        Format::Verilog => input.to_verilog(),
+        Format::Json => input.to_json(),
+    })
+}
+
+impl OperationClassification {
+    const fn color(&self) -> &'static str {
+        match self {
+            OperationClassification::Suspicious => "salmon",
+            OperationClassification::Output => "palegreen",
+            OperationClassification::InputXor => "lightblue",
+            OperationClassification::InputAnd => "lightyellow",
+            OperationClassification::Carry => "orange",
+            OperationClassification::Remainder => "plum",
+        }
+    }
+}
+
+/// Verilog name of a wire, gate outputs get a prefix so they can't clash with keywords like `or`.
+/// Names with other characters than letters, digits and `_` become escaped identifiers.
+fn verilog_name(key: &Key) -> String {
+    match key {
+        X(i) => format!("x[{i}]"),
+        Y(i) => format!("y[{i}]"),
+        Z(i) => format!("z[{i}]"),
+        O(name) if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+            format!("w_{name}")
+        }
+        O(name) => format!("\\w_{name} "),
+    }
+}
+
+/// Quoted DOT id, also usable inside a quoted label.
+fn dot_escape(key: &Key) -> String {
+    key.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Contents of a JSON string holding `key`, without the quotes.
+fn json_escape(key: &Key) -> String {
+    let mut escaped = String::new();
+    for c in key.to_string().chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Input {
+    /// Gate outputs in name order, with their classification.
+    fn classified(&self) -> Vec<(&Key, OperationClassification)> {
+        let mut cache = FxHashMap::default();
+        self.operations
+            .iter()
+            .sorted_by(|a, b| a.0.cmp(b.0))
+            .map(|(key, operation)| (key, operation.classify(self, &mut cache)))
+            .collect()
+    }
+
+    /// Number of `x` and `y` bits read by the gates.
+    fn input_width(&self) -> usize {
+        self.operations
+            .values()
+            .flat_map(|o| [&o.k1, &o.k2])
+            .filter_map(|k| match k {
+                X(i) | Y(i) => Some(i + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub(super) fn to_dot(&self) -> String {
+        let suspicious = find_suspicious_nodes(self);
+        let mut dot =
+            String::from("digraph circuit {\n    rankdir=LR;\n    node [style=filled];\n");
+        let inputs: FxHashSet<&Key> = self
+            .operations
+            .values()
+            .flat_map(|o| [&o.k1, &o.k2])
+            .filter(|k| k.is_input())
+            .collect();
+        for key in inputs.into_iter().sorted() {
+            let _ = writeln!(dot, "    \"{key}\" [shape=box, fillcolor=white];");
+        }
+        for (key, classification) in self.classified() {
+            let operation = &self.operations[key];
+            let highlight = if suspicious.contains(key) {
+                ", color=red, penwidth=3"
+            } else {
+                ""
+            };
+            let id = dot_escape(key);
+            let _ = writeln!(
+                dot,
+                "    \"{id}\" [label=\"{id}\\n{}\", fillcolor={}{highlight}];",
+                operation.op,
+                classification.color()
+            );
+            for input in [&operation.k1, &operation.k2] {
+                let _ = writeln!(dot, "    \"{}\" -> \"{id}\";", dot_escape(input));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub(super) fn to_verilog(&self) -> String {
+        let mut verilog = format!(
+            "module adder (\n    input [{}:0] x,\n    input [{0}:0] y,\n    output [{}:0] z\n);\n",
+            self.input_width().max(1) - 1,
+            self.z_last_index
+        );
+        let keys = self.operations.keys().sorted().collect_vec();
+        for key in keys.iter().filter(|k| matches!(k, O(_))) {
+            let _ = writeln!(verilog, "    wire {};", verilog_name(key));
+        }
+        for key in keys {
+            let operation = &self.operations[key];
+            let op = match operation.op {
+                Op::And => "&",
+                Op::Or => "|",
+                Op::Xor => "^",
+            };
+            let _ = writeln!(
+                verilog,
+                "    assign {} = {} {op} {};",
+                verilog_name(key),
+                verilog_name(&operation.k1),
+                verilog_name(&operation.k2)
+            );
+        }
+        verilog.push_str("endmodule\n");
+        verilog
+    }
+
+    pub(super) fn to_json(&self) -> String {
+        let suspicious = find_suspicious_nodes(self);
+        let gates = self
+            .classified()
+            .into_iter()
+            .map(|(key, classification)| {
+                let operation = &self.operations[key];
+                format!(
+                    r#"{{"output":"{}","op":"{}","inputs":["{}","{}"],"class":"{classification:?}","suspicious":{}}}"#,
+                    json_escape(key),
+                    operation.op,
+                    json_escape(&operation.k1),
+                    json_escape(&operation.k2),
+                    suspicious.contains(key)
+                )
+            })
+            .join(",");
+        format!(
+            r#"{{"x":{},"y":{},"input_bits":{},"output_bits":{},"gates":[{gates}]}}"#,
+            self.x,
+            self.y,
+            self.input_width(),
+            self.z_last_index + 1
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::netlist::tests::ripple_carry_adder;
+    use super::*;
+
+    #[test]
+    fn exports_dot() {
+        let source = ripple_carry_adder(2);
+        let dot = export(&source, Format::Dot, &[]).unwrap();
+        assert!(dot.starts_with("digraph circuit {\n"));
+        assert!(dot.contains("    \"x00\" [shape=box, fillcolor=white];\n"));
+        assert!(dot.contains("    \"z00\" [label=\"z00\\nXOR\", fillcolor=palegreen];\n"));
+        assert!(dot.contains("    \"c00\" -> \"z01\";\n"));
+        assert!(!dot.contains("color=red"));
+
+        let swapped = export(&source, Format::Dot, &[("z01", "a01")]).unwrap();
+        assert!(swapped.contains(
+            "    \"z01\" [label=\"z01\\nAND\", fillcolor=salmon, color=red, penwidth=3];\n"
+        ));
+    }
+
+    #[test]
+    fn exports_verilog() {
+        let verilog = export(&ripple_carry_adder(2), Format::Verilog, &[]).unwrap();
+        assert_eq!(
+            verilog,
+            "module adder (
+    input [1:0] x,
+    input [1:0] y,
+    output [2:0] z
+);
+    wire w_a01;
+    wire w_c00;
+    wire w_r01;
+    wire w_s01;
+    assign w_a01 = y[1] & x[1];
+    assign w_c00 = x[0] & y[0];
+    assign w_r01 = w_s01 & w_c00;
+    assign w_s01 = x[1] ^ y[1];
+    assign z[0] = x[0] ^ y[0];
+    assign z[1] = w_c00 ^ w_s01;
+    assign z[2] = w_a01 | w_r01;
+endmodule
+"
+        );
+    }
+
+    #[test]
+    fn exports_json() {
+        let source = ripple_carry_adder(2);
+        let json = export(&source, Format::Json, &[("z01", "a01")]).unwrap();
+        assert!(json.starts_with(r#"{"x":0,"y":0,"input_bits":2,"output_bits":3,"gates":["#));
+        assert!(json.contains(
+            r#"{"output":"a01","op":"XOR","inputs":["c00","s01"],"class":"Suspicious","suspicious":true}"#
+        ));
+        assert_eq!(
+            export(
+                "x00: 1\n\nx00 AND y00 -> z00",
+                Format::Json,
+                &[("z00", "abc")]
+            ),
+            Err(ExportError::NotAGate("abc".to_string()))
+        );
+        for wire in ["xab", ""] {
+            assert_eq!(
+                export(&source, Format::Json, &[(wire, "z00")]),
+                Err(ExportError::NotAGate(wire.to_string()))
+            );
+        }
+        assert_eq!(
+            export(&source, Format::Json, &[("z02", "a01")]),
+            Err(ExportError::Cycle("a01".to_string()))
+        );
+        // The cycle is found even though an earlier output reads an undriven wire
+        assert_eq!(
+            export(
+                "x00: 1\n\nfoo XOR x00 -> z00\nppp OR qqq -> ppp",
+                Format::Json,
+                &[]
+            ),
+            Err(ExportError::Cycle("ppp".to_string()))
+        );
+    }
+
+    #[test]
+    fn escapes_wire_names() {
+        let source = "x00: 1\ny00: 0\n\nx00 XOR y00 -> 1\"a\\b.c\n1\"a\\b.c OR y00 -> z00\n";
+        let dot = export(source, Format::Dot, &[]).unwrap();
+        assert!(dot.contains(r#"    "1\"a\\b.c" -> "z00";"#));
+        let verilog = export(source, Format::Verilog, &[]).unwrap();
+        assert!(verilog.contains("    wire \\w_1\"a\\b.c ;\n"));
+        assert!(verilog.contains("    assign z[0] = \\w_1\"a\\b.c  | y[0];\n"));
+        let json = export(source, Format::Json, &[]).unwrap();
+        assert!(json.contains(r#""inputs":["1\"a\\b.c","y00"]"#));
+    }
+}
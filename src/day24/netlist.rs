@@ -0,0 +1,280 @@
+use super::Key::{X, Y, Z};
+use super::{Input, Key, Op};
+use fxhash::{FxHashMap, FxHashSet};
+use std::fmt::{Display, Formatter};
+
+/// Why the gates don't form a circuit that can be evaluated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum NetlistError {
+    /// The wire depends on itself, e.g. after swapping outputs.
+    Cycle(Key),
+    /// The wire is read but no gate drives it.
+    Undriven(Key),
+}
+
+impl Display for NetlistError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetlistError::Cycle(key) => write!(f, "Wire {key} depends on itself"),
+            NetlistError::Undriven(key) => write!(f, "Wire {key} isn't driven by any gate"),
+        }
+    }
+}
+
+impl std::error::Error for NetlistError {}
+
+#[derive(Copy, Clone, Debug)]
+struct Gate {
+    a: usize,
+    b: usize,
+    op: Op,
+    out: usize,
+}
+
+/// Gates compiled to wire indices and sorted so every gate comes after the gates it reads.
+#[derive(Clone, Debug)]
+pub(super) struct Netlist {
+    wires: Vec<Key>,
+    gates: Vec<Gate>,
+    /// Input wires with their key, which tells the bit they read.
+    inputs: Vec<(usize, Key)>,
+    /// Wire of each output bit.
+    z: Vec<usize>,
+}
+
+struct Builder<'a> {
+    input: &'a Input,
+    index: FxHashMap<Key, usize>,
+    visiting: FxHashSet<Key>,
+    /// First wire read without a driver, reported once no cycle was found.
+    undriven: Option<Key>,
+    netlist: Netlist,
+}
+
+impl Builder<'_> {
+    fn add_wire(&mut self, key: &Key) -> usize {
+        let wire = self.netlist.wires.len();
+        self.netlist.wires.push(key.clone());
+        self.index.insert(key.clone(), wire);
+        wire
+    }
+
+    /// Wire for `key`, adding the gates it depends on first.
+    fn visit(&mut self, key: &Key) -> Result<usize, NetlistError> {
+        if let Some(&wire) = self.index.get(key) {
+            return Ok(wire);
+        }
+        let Some(operation) = self.input.operations.get(key) else {
+            return if key.is_input() {
+                let wire = self.add_wire(key);
+                self.netlist.inputs.push((wire, key.clone()));
+                Ok(wire)
+            } else {
+                self.undriven.get_or_insert_with(|| key.clone());
+                Ok(self.add_wire(key))
+            };
+        };
+        if !self.visiting.insert(key.clone()) {
+            return Err(NetlistError::Cycle(key.clone()));
+        }
+        let a = self.visit(&operation.k1)?;
+        let b = self.visit(&operation.k2)?;
+        self.visiting.remove(key);
+        let out = self.add_wire(key);
+        self.netlist.gates.push(Gate {
+            a,
+            b,
+            op: operation.op,
+            out,
+        });
+        Ok(out)
+    }
+}
+
+impl Netlist {
+    /// Compiles every gate of `input`, failing on cycles, then on wires nothing drives.
+    pub fn compile(input: &Input) -> Result<Self, NetlistError> {
+        let mut builder = Builder {
+            input,
+            index: FxHashMap::default(),
+            visiting: FxHashSet::default(),
+            undriven: None,
+            netlist: Netlist {
+                wires: Vec::new(),
+                gates: Vec::new(),
+                inputs: Vec::new(),
+                z: Vec::new(),
+            },
+        };
+        for i in 0..=input.z_last_index {
+            let wire = builder.visit(&Z(i))?;
+            builder.netlist.z.push(wire);
+        }
+        let mut rest: Vec<&Key> = input.operations.keys().collect();
+        rest.sort();
+        for key in rest {
+            builder.visit(key)?;
+        }
+        match builder.undriven {
+            Some(key) => Err(NetlistError::Undriven(key)),
+            None => Ok(builder.netlist),
+        }
+    }
+
+    /// Evaluates 64 input vectors at once: bit `j` of `x[i]` is bit `i` of the `j`th `x`, and the
+    /// output is laid out the same way. Missing input bits are 0.
+    pub fn evaluate_lanes(&self, x: &[u64], y: &[u64]) -> Vec<u64> {
+        let mut values = vec![0u64; self.wires.len()];
+        for (wire, key) in &self.inputs {
+            values[*wire] = match key {
+                X(i) => x.get(*i).copied().unwrap_or(0),
+                Y(i) => y.get(*i).copied().unwrap_or(0),
+                _ => unreachable!("Only x and y are inputs"),
+            };
+        }
+        for gate in &self.gates {
+            let (a, b) = (values[gate.a], values[gate.b]);
+            values[gate.out] = match gate.op {
+                Op::And => a & b,
+                Op::Or => a | b,
+                Op::Xor => a ^ b,
+            };
+        }
+        self.z.iter().map(|&wire| values[wire]).collect()
+    }
+
+    /// Output for each `(x, y)`, 64 pairs per pass. Output bits past 63 are dropped.
+    pub fn evaluate_many(&self, pairs: &[(u64, u64)]) -> Vec<u64> {
+        let mut result = Vec::with_capacity(pairs.len());
+        for chunk in pairs.chunks(64) {
+            let mut x = [0u64; 64];
+            let mut y = [0u64; 64];
+            for (lane, &(xv, yv)) in chunk.iter().enumerate() {
+                for bit in 0..64 {
+                    x[bit] |= (xv >> bit & 1) << lane;
+                    y[bit] |= (yv >> bit & 1) << lane;
+                }
+            }
+            let z = self.evaluate_lanes(&x, &y);
+            result.extend((0..chunk.len()).map(|lane| {
+                z.iter()
+                    .take(64)
+                    .enumerate()
+                    .fold(0, |acc, (bit, lanes)| acc | (lanes >> lane & 1) << bit)
+            }));
+        }
+        result
+    }
+
+    pub fn evaluate(&self, x: u64, y: u64) -> u64 {
+        self.evaluate_many(&[(x, y)])[0]
+    }
+
+    /// Whether the circuit adds correctly for every combination of bits `i - 1` and `i` of `x`
+    /// and `y`, and when a carry has to ripple from bit 0 up to bit `i`, for each `i` below
+    /// `bits`. Sums have to fit in a `u64`, so at most the low 63 bits are checked.
+    pub fn is_adder(&self, bits: usize) -> bool {
+        let pairs: Vec<(u64, u64)> = (0..bits.min(63))
+            .flat_map(|i| {
+                let value = 1u64 << i;
+                let window = move |m: u64| ((m & 1) * value) | ((m >> 1 & 1) * (value >> 1));
+                let ripple = value | (value - 1);
+                (0..16)
+                    .map(move |m| (window(m), window(m >> 2)))
+                    .chain([(ripple, 1), (1, ripple)])
+            })
+            .collect();
+        self.evaluate_many(&pairs)
+            .iter()
+            .zip(&pairs)
+            .all(|(&z, &(x, y))| z == x + y)
+    }
+}
+
+#[cfg(test)]
+impl Netlist {
+    pub fn gate_count(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Number of output bits.
+    pub fn width(&self) -> usize {
+        self.z.len()
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::super::parse;
+    use super::*;
+    use std::fmt::Write;
+
+    /// Gates of a correct `bits`-bit ripple-carry adder, in puzzle input format.
+    pub(in super::super) fn ripple_carry_adder(bits: usize) -> String {
+        let mut text = String::new();
+        for i in 0..bits {
+            let _ = writeln!(text, "x{i:02}: 0\ny{i:02}: 0");
+        }
+        text.push('\n');
+        let carry = |i: usize| {
+            if i + 1 == bits {
+                format!("z{bits:02}")
+            } else {
+                format!("c{i:02}")
+            }
+        };
+        let _ = writeln!(text, "x00 XOR y00 -> z00\nx00 AND y00 -> {}", carry(0));
+        for i in 1..bits {
+            let _ = writeln!(
+                text,
+                "x{i:02} XOR y{i:02} -> s{i:02}\n\
+                 y{i:02} AND x{i:02} -> a{i:02}\n\
+                 {c} XOR s{i:02} -> z{i:02}\n\
+                 s{i:02} AND {c} -> r{i:02}\n\
+                 a{i:02} OR r{i:02} -> {out}",
+                c = carry(i - 1),
+                out = carry(i)
+            );
+        }
+        text
+    }
+
+    #[test]
+    fn evaluates_lanes() {
+        let input = parse(&ripple_carry_adder(8));
+        let netlist = Netlist::compile(&input).unwrap();
+        assert_eq!(netlist.width(), 9);
+        assert_eq!(netlist.gate_count(), 5 * 7 + 2);
+        let pairs: Vec<(u64, u64)> = (0..200).map(|i| (i * 37 % 256, i * 91 % 256)).collect();
+        let sums = netlist.evaluate_many(&pairs);
+        assert!(pairs.iter().zip(&sums).all(|(&(x, y), &z)| z == x + y));
+        assert!(netlist.is_adder(8));
+    }
+
+    #[test]
+    fn detects_broken_circuits() {
+        let input = parse(&ripple_carry_adder(4));
+        let swapped = input
+            .clone()
+            .with_swaps(&[("z01".parse::<Key>().unwrap(), "a01".parse::<Key>().unwrap())]);
+        let netlist = Netlist::compile(&swapped).unwrap();
+        assert!(!netlist.is_adder(4));
+
+        // Only shows when bit 1 carries into a bit 2 that propagates
+        let swapped = input
+            .clone()
+            .with_swaps(&[("r01".parse::<Key>().unwrap(), "s02".parse::<Key>().unwrap())]);
+        let netlist = Netlist::compile(&swapped).unwrap();
+        assert!(!netlist.is_adder(4));
+
+        let wide = parse(&ripple_carry_adder(64));
+        assert!(Netlist::compile(&wide).unwrap().is_adder(64));
+
+        let cyclic =
+            input.with_swaps(&[("s02".parse::<Key>().unwrap(), "c02".parse::<Key>().unwrap())]);
+        assert!(matches!(
+            Netlist::compile(&cyclic),
+            Err(NetlistError::Cycle(_))
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+use super::netlist::Netlist;
+use super::Key::{X, Y, Z};
+use super::Op::{And, Or, Xor};
+use super::{Input, Key, Op, Operation};
+use fxhash::FxHashSet;
+use std::fmt::{Display, Formatter};
+
+/// Two gate outputs to exchange, and why.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct Swap {
+    pub a: Key,
+    pub b: Key,
+    pub reason: String,
+}
+
+impl Display for Swap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <-> {}: {}", self.a, self.b, self.reason)
+    }
+}
+
+/// Why the circuit couldn't be turned into a ripple-carry adder by swapping outputs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum RepairError {
+    /// Only `z00` is driven, so there is no carry out and the template doesn't apply.
+    NoCarryOut,
+    /// The template needs a gate that no swap can create.
+    MissingGate { bit: usize, gate: String },
+    /// The template asked to move a wire that was already swapped.
+    SwappedTwice(Key),
+    /// Every bit matches the template, but the circuit still doesn't add.
+    NotAnAdder,
+}
+
+impl Display for RepairError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RepairError::NoCarryOut => write!(f, "Circuit has no output past z00 for a carry"),
+            RepairError::MissingGate { bit, gate } => write!(f, "Bit {bit} has no {gate}"),
+            RepairError::SwappedTwice(key) => write!(f, "Wire {key} would be swapped twice"),
+            RepairError::NotAnAdder => write!(f, "Circuit fits the template but doesn't add"),
+        }
+    }
+}
+
+impl std::error::Error for RepairError {}
+
+/// Gate lookups for matching the circuit against the template.
+struct Circuit<'a> {
+    input: &'a Input,
+}
+
+impl Circuit<'_> {
+    /// Output of the `op` gate reading exactly `a` and `b`.
+    fn find(&self, op: Op, a: &Key, b: &Key) -> Option<&Key> {
+        let reads = |o: &Operation| (&o.k1 == a && &o.k2 == b) || (&o.k1 == b && &o.k2 == a);
+        self.input
+            .operations
+            .iter()
+            .find(|(_, o)| o.op == op && reads(o))
+            .map(|(key, _)| key)
+    }
+
+    /// Output of the `op` gate reading `a` and `b`, which has to exist.
+    fn output(&self, bit: usize, op: Op, a: &Key, b: &Key) -> Result<Key, RepairError> {
+        self.find(op, a, b)
+            .cloned()
+            .ok_or_else(|| RepairError::MissingGate {
+                bit,
+                gate: format!("{op} of {a} and {b}"),
+            })
+    }
+
+    /// Other input of an `op` gate reading `key` and no inputs.
+    fn partner(&self, op: Op, key: &Key) -> Option<Key> {
+        self.input.operations.values().find_map(|o| {
+            if o.op != op || o.k1.is_input() || o.k2.is_input() {
+                None
+            } else if &o.k1 == key {
+                Some(o.k2.clone())
+            } else if &o.k2 == key {
+                Some(o.k1.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// How `key` is driven, e.g. `AND of x07 and y07`.
+    fn driver(&self, key: &Key) -> String {
+        match self.input.operations.get(key) {
+            Some(o) => format!("{} of {} and {}", o.op, o.k1, o.k2),
+            None => "nothing".to_string(),
+        }
+    }
+
+    /// First swap the ripple-carry template calls for, going up from bit 0, or `None` if every
+    /// bit fits.
+    ///
+    /// Bit `i` has to be `z = carry ^ (x ^ y)` with the carry out `(x & y) | (carry & (x ^ y))`.
+    /// Gates reading `x` and `y` can only have their outputs swapped, so they are always found,
+    /// and the trusted carry from the bit below tells which input of a mismatched gate is wrong.
+    /// `bits` has to be at least 1, the carry out of the top bit is `z{bits}`.
+    fn next_swap(&self, bits: usize) -> Result<Option<Swap>, RepairError> {
+        let swap = |a: &Key, b: &Key, reason: String| {
+            Ok(Some(Swap {
+                a: a.clone(),
+                b: b.clone(),
+                reason,
+            }))
+        };
+
+        let z = Z(0);
+        let sum = self.output(0, Xor, &X(0), &Y(0))?;
+        if sum != z {
+            let reason = format!(
+                "{z} driven by {}, expected XOR of x00 and y00",
+                self.driver(&z)
+            );
+            return swap(&z, &sum, reason);
+        }
+        let mut carry = self.output(0, And, &X(0), &Y(0))?;
+
+        for i in 1..bits {
+            let z = Z(i);
+            let half = self.output(i, Xor, &X(i), &Y(i))?;
+            let both = self.output(i, And, &X(i), &Y(i))?;
+            match self.find(Xor, &carry, &half) {
+                Some(sum) if sum != &z => {
+                    let reason = format!(
+                        "{z} driven by {}, expected XOR of carry and x{i:02}^y{i:02}",
+                        self.driver(&z)
+                    );
+                    return swap(&z, sum, reason);
+                }
+                Some(_) => {}
+                None => {
+                    if let Some(other) = self.partner(Xor, &carry) {
+                        let reason = format!(
+                            "{other} feeds the XOR for {z} with carry {carry}, expected x{i:02}^y{i:02}"
+                        );
+                        return swap(&half, &other, reason);
+                    }
+                    if let Some(other) = self.partner(Xor, &half) {
+                        let reason = format!(
+                            "{other} feeds the XOR for {z} with x{i:02}^y{i:02}, expected carry {carry}"
+                        );
+                        return swap(&carry, &other, reason);
+                    }
+                    return Err(RepairError::MissingGate {
+                        bit: i,
+                        gate: format!("XOR of {carry} and {half}"),
+                    });
+                }
+            }
+
+            let propagated = self.output(i, And, &carry, &half)?;
+            carry = match self.find(Or, &both, &propagated) {
+                Some(next) => next.clone(),
+                None => {
+                    if let Some(other) = self.partner(Or, &both) {
+                        let reason = format!(
+                            "{other} feeds the carry OR of bit {i}, expected carry AND x{i:02}^y{i:02}"
+                        );
+                        return swap(&propagated, &other, reason);
+                    }
+                    if let Some(other) = self.partner(Or, &propagated) {
+                        let reason = format!(
+                            "{other} feeds the carry OR of bit {i}, expected x{i:02}&y{i:02}"
+                        );
+                        return swap(&both, &other, reason);
+                    }
+                    return Err(RepairError::MissingGate {
+                        bit: i,
+                        gate: format!("OR of {both} and {propagated}"),
+                    });
+                }
+            };
+        }
+
+        let z = Z(bits);
+        if carry != z {
+            let reason = format!(
+                "{z} driven by {}, expected the carry out of bit {}",
+                self.driver(&z),
+                bits - 1
+            );
+            return swap(&z, &carry, reason);
+        }
+        Ok(None)
+    }
+}
+
+/// Swaps that turn the circuit into a ripple-carry adder of its `x` and `y` bits, in the order
+/// they were found. Each swap is made before looking for the next, so any number of them can
+/// be found, and no wire is swapped twice.
+pub(super) fn repair(input: &Input) -> Result<Vec<Swap>, RepairError> {
+    let bits = input.z_last_index;
+    if bits == 0 {
+        return Err(RepairError::NoCarryOut);
+    }
+    let mut input = input.clone();
+    let mut swaps = Vec::new();
+    let mut swapped = FxHashSet::default();
+    while let Some(swap) = (Circuit { input: &input }).next_swap(bits)? {
+        for key in [&swap.a, &swap.b] {
+            if !swapped.insert(key.clone()) {
+                return Err(RepairError::SwappedTwice(key.clone()));
+            }
+        }
+        input = input.with_swaps(&[(swap.a.clone(), swap.b.clone())]);
+        swaps.push(swap);
+    }
+    if !Netlist::compile(&input).is_ok_and(|netlist| netlist.is_adder(bits)) {
+        return Err(RepairError::NotAnAdder);
+    }
+    Ok(swaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::netlist::tests::ripple_carry_adder;
+    use super::super::parse;
+    use super::*;
+    use itertools::Itertools;
+
+    fn broken_adder(bits: usize, swaps: &[(&str, &str)]) -> Input {
+        let swaps: Vec<(Key, Key)> = swaps
+            .iter()
+            .map(|&(a, b)| (a.parse::<Key>().unwrap(), b.parse::<Key>().unwrap()))
+            .collect();
+        parse(&ripple_carry_adder(bits)).with_swaps(&swaps)
+    }
+
+    fn swapped_wires(swaps: &[Swap]) -> String {
+        swaps
+            .iter()
+            .flat_map(|s| [s.a.clone(), s.b.clone()])
+            .sorted()
+            .join(",")
+    }
+
+    #[test]
+    fn correct_adder_needs_no_swaps() {
+        assert_eq!(repair(&broken_adder(6, &[])), Ok(vec![]));
+        assert_eq!(repair(&broken_adder(1, &[])), Ok(vec![]));
+    }
+
+    #[test]
+    fn finds_any_number_of_swaps() {
+        let input = broken_adder(12, &[("z03", "a03")]);
+        let swaps = repair(&input).unwrap();
+        assert_eq!(swapped_wires(&swaps), "a03,z03");
+        assert_eq!(
+            swaps[0].reason,
+            "z03 driven by AND of y03 and x03, expected XOR of carry and x03^y03"
+        );
+
+        let input = broken_adder(
+            12,
+            &[
+                ("s05", "a05"),
+                ("c02", "z04"),
+                ("r08", "z09"),
+                ("z00", "a01"),
+                ("z11", "z12"),
+            ],
+        );
+        let swaps = repair(&input).unwrap();
+        assert_eq!(
+            swapped_wires(&swaps),
+            "a01,a05,c02,r08,s05,z00,z04,z09,z11,z12"
+        );
+        assert_eq!(
+            swaps
+                .iter()
+                .find(|s| s.b == "s05".parse::<Key>().unwrap())
+                .unwrap()
+                .reason,
+            "s05 feeds the XOR for z05 with carry c04, expected x05^y05"
+        );
+    }
+
+    #[test]
+    fn reports_unfixable_circuits() {
+        let one_bit = parse("x00: 1\ny00: 1\n\nx00 XOR y00 -> z00\nx00 AND y00 -> c00");
+        assert_eq!(repair(&one_bit), Err(RepairError::NoCarryOut));
+
+        let mut input = broken_adder(4, &[]);
+        let gate = input
+            .operations
+            .get_mut(&"r02".parse::<Key>().unwrap())
+            .unwrap();
+        gate.op = Or;
+        assert_eq!(
+            repair(&input),
+            Err(RepairError::MissingGate {
+                bit: 2,
+                gate: "AND of c01 and s02".to_string()
+            })
+        );
+    }
+}
@@ -1,6 +1,5 @@
-use crate::utils::c2::{C2, C2_8_NEIGHBORS};
+use crate::utils::c2::{SparseGrid, C2, C2_8_NEIGHBORS};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashMap;
 use std::fmt::Display;
 
 // CodSpeed compatibility
@@ -15,7 +14,7 @@ pub fn part2(input: &str) -> impl Display {
 // CodSpeed compatibility end
 
 struct WordField {
-    letters: HashMap<C2, char>,
+    letters: SparseGrid<char>,
 }
 
 static TOP_LEFT: C2 = C2::new(-1, -1);
@@ -27,12 +26,7 @@ static XMAS: [char; 4] = ['X', 'M', 'A', 'S'];
 
 impl WordField {
     fn parse(input: String) -> Self {
-        let mut letters = HashMap::new();
-        for (y, line) in input.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                letters.insert(C2::new(x as i32, y as i32), c);
-            }
-        }
+        let letters = SparseGrid::from_string(&input, Some);
         Self { letters }
     }
 
@@ -45,20 +39,15 @@ impl WordField {
             return 0;
         }
 
-        let mut count = 0;
-        'words: for d in C2_8_NEIGHBORS {
-            for (k, l) in XMAS.iter().enumerate().skip(1) {
-                if let Some(char) = self.letters.get(&(*c + (d * k as i32))) {
-                    if char != l {
-                        continue 'words;
-                    }
-                } else {
-                    continue 'words;
-                }
-            }
-            count += 1;
-        }
-        count
+        C2_8_NEIGHBORS
+            .iter()
+            .filter(|d| {
+                c.ray(**d)
+                    .zip(XMAS.iter())
+                    .skip(1)
+                    .all(|(p, l)| self.letters.get(&p) == Some(l))
+            })
+            .count() as i32
     }
 
     fn is_mas(&self, coord: &C2) -> bool {
@@ -1,8 +1,9 @@
-use crate::utils::c2::C2;
+use crate::utils::c2::{Direction, SparseGrid, C2};
+use crate::utils::cycle::{find_cycle_hashed, Cycle};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::fmt::Display;
 
-use fxhash::{FxHashMap, FxHashSet};
+use fxhash::FxHashSet;
 
 // CodSpeed compatibility
 #[allow(dead_code)]
@@ -16,13 +17,13 @@ pub fn part2(input: &str) -> impl Display {
 // CodSpeed compatibility end
 
 struct LabMap {
-    pub tiles: FxHashMap<C2, char>,
+    pub tiles: SparseGrid<char>,
     pub guard_start: C2,
     pub guard_direction: C2,
 }
 #[aoc_generator(day6)]
 fn parse(input: &str) -> LabMap {
-    let mut tiles = FxHashMap::default();
+    let mut tiles = SparseGrid::new();
     let mut guard_start = C2::ZERO;
     let mut guard_direction = C2::ZERO;
 
@@ -31,29 +32,17 @@ fn parse(input: &str) -> LabMap {
             let coord = C2::new(x as i32, y as i32);
             match c {
                 '.' | '#' => {
-                    tiles.insert(coord, c);
+                    tiles.set(&coord, c);
                 }
-                '^' => {
-                    guard_start = coord;
-                    guard_direction = C2::new(0, -1);
-                    tiles.insert(coord, '.');
+                arrow => {
+                    if let Some(direction) = Direction::from_arrow(arrow) {
+                        guard_start = coord;
+                        guard_direction = direction.to_c2();
+                        tiles.set(&coord, '.');
+                    } else {
+                        unimplemented!("Unrecognized character: '{}'", c)
+                    }
                 }
-                '>' => {
-                    guard_start = coord;
-                    guard_direction = C2::new(1, 0);
-                    tiles.insert(coord, '.');
-                }
-                'v' => {
-                    guard_start = coord;
-                    guard_direction = C2::new(0, 1);
-                    tiles.insert(coord, '.');
-                }
-                '<' => {
-                    guard_start = coord;
-                    guard_direction = C2::new(-1, 0);
-                    tiles.insert(coord, '.');
-                }
-                _ => unimplemented!("Unrecognized character: '{}'", c),
             }
         }
     }
@@ -65,70 +54,55 @@ fn parse(input: &str) -> LabMap {
     }
 }
 
+type Guard = (C2, C2);
+
+impl LabMap {
+    /// Guard states until leaving the map, plus the loop if the guard never leaves.
+    fn patrol(&self, from: Guard, obstacle: Option<C2>) -> (Vec<Guard>, Option<Cycle>) {
+        find_cycle_hashed(from, |&(position, direction)| {
+            let next_position = position + direction;
+            if !self.tiles.contains(&next_position) {
+                None
+            } else if self.tiles.get(&next_position) == Some(&'#')
+                || Some(next_position) == obstacle
+            {
+                Some((position, direction.rotate_right()))
+            } else {
+                Some((next_position, direction))
+            }
+        })
+    }
+}
+
 #[aoc(day6, part1)]
 fn part1_solution(input: &LabMap) -> i32 {
-    let mut visited: FxHashSet<C2> = FxHashSet::default();
-
-    let mut guard_position = input.guard_start;
-    let mut guard_direction = input.guard_direction;
-
-    while input.tiles.contains_key(&guard_position) {
-        visited.insert(guard_position);
-        let next_position = guard_position + guard_direction;
-        if input.tiles.get(&next_position) == Some(&'#') {
-            guard_direction = guard_direction.rotate_right();
-        } else {
-            guard_position = next_position;
-        }
-    }
+    let (path, _) = input.patrol((input.guard_start, input.guard_direction), None);
 
-    visited.len() as i32
+    path.iter()
+        .map(|(position, _)| *position)
+        .collect::<FxHashSet<C2>>()
+        .len() as i32
 }
 
 #[aoc(day6, part2)]
 fn part2_solution(input: &LabMap) -> i32 {
-    let mut path: Vec<(C2, C2)> = Vec::new();
-    let mut guard_position = input.guard_start;
-    let mut guard_direction = input.guard_direction;
-
-    while input.tiles.contains_key(&guard_position) {
-        path.push((guard_position, guard_direction));
-        let next_position = guard_position + guard_direction;
-        if input.tiles.get(&next_position) == Some(&'#') {
-            guard_direction = guard_direction.rotate_right();
-        } else {
-            guard_position = next_position;
-        }
-    }
+    let (path, _) = input.patrol((input.guard_start, input.guard_direction), None);
 
     let mut successful_obstacles: FxHashSet<C2> = FxHashSet::default();
     let mut failed_obstacles: FxHashSet<C2> = FxHashSet::default();
 
-    'path: for i in 1..path.len() {
+    for i in 1..path.len() {
         let new_obstacle = path[i].0;
         if successful_obstacles.contains(&new_obstacle) || failed_obstacles.contains(&new_obstacle)
         {
-            continue 'path;
+            continue;
         }
-        guard_position = path[i - 1].0;
-        guard_direction = path[i - 1].1;
-
-        let mut visited: FxHashSet<(C2, C2)> = path.iter().take(i - 1).cloned().collect();
 
-        while input.tiles.contains_key(&guard_position) {
-            if !visited.insert((guard_position, guard_direction)) {
-                successful_obstacles.insert(new_obstacle);
-                continue 'path;
-            }
-            let next_position = guard_position + guard_direction;
-            if input.tiles.get(&next_position) == Some(&'#') || next_position == new_obstacle {
-                guard_direction = guard_direction.rotate_right();
-            } else {
-                guard_position = next_position;
-            }
+        if input.patrol(path[i - 1], Some(new_obstacle)).1.is_some() {
+            successful_obstacles.insert(new_obstacle);
+        } else {
+            failed_obstacles.insert(new_obstacle);
         }
-
-        failed_obstacles.insert(new_obstacle);
     }
 
     successful_obstacles.len() as i32
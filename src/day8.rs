@@ -76,19 +76,7 @@ fn part2_solution(input: &Field) -> usize {
         for a in locations {
             for b in locations {
                 if a != b {
-                    let a = *a;
-                    let b = *b;
-                    let delta = a - b;
-
-                    let mut current = a;
-                    while current.x >= 0
-                        && current.y >= 0
-                        && current.x < input.border.x
-                        && current.y < input.border.y
-                    {
-                        antinodes.insert(current);
-                        current = current + delta;
-                    }
+                    antinodes.extend(a.ray_within(*a - *b, C2::ZERO, input.border));
                 }
             }
         }
@@ -1,3 +1,4 @@
+use crate::utils::interval::IntervalSet;
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::clone::Clone;
 use std::fmt::Display;
@@ -110,65 +111,41 @@ fn part1_solution(input: &Vec<Entry>) -> u64 {
     checksum(&data)
 }
 
-fn merge_free_spaces(vec: Vec<Entry>) -> Vec<Entry> {
-    let mut result = Vec::new();
-
-    for item in vec {
-        match (result.last_mut(), item) {
-            (Some(Entry::Free(existing)), Entry::Free(new)) => {
-                *existing += new;
+#[aoc(day9, part2)]
+fn part2_solution(input: &[Entry]) -> u64 {
+    let mut free = IntervalSet::new();
+    let mut files = Vec::new();
+    let mut position = 0u64;
+    for entry in input {
+        match entry {
+            Entry::Taken(file) => {
+                files.push((*file, position));
+                position += file.length as u64;
+            }
+            Entry::Free(space) => {
+                free.insert(position..position + *space as u64);
+                position += *space as u64;
             }
-            (_, new_item) => result.push(new_item),
         }
     }
 
-    result
-}
-
-#[aoc(day9, part2)]
-fn part2_solution(input: &[Entry]) -> u64 {
-    let mut data = input.to_owned();
-    data = merge_free_spaces(data);
-    let mut to_process: Vec<&File> = input
+    files.sort_by_key(|(file, _)| file.id);
+    files
         .iter()
-        .filter_map(|x| match x {
-            Entry::Taken(f) => Some(f),
-            Entry::Free(_) => None,
-        })
-        .collect();
-
-    to_process.sort_by(|a, b| a.id.cmp(&b.id));
-    to_process.reverse();
-
-    for f in to_process {
-        let file_index = data
-            .iter()
-            .position(|x| *x == Entry::Taken(*f))
-            .expect("Should be present");
-
-        let empty_index = data.iter().position(|x| match x {
-            Entry::Taken(_) => false,
-            Entry::Free(size) => *size >= f.length,
-        });
-
-        if let Some(empty_index) = empty_index {
-            if empty_index < file_index {
-                let file = data.remove(file_index);
-                let empty = data.remove(empty_index);
-                data.insert(empty_index, file);
-                data.insert(file_index, Entry::Free(f.length));
-                if let Entry::Free(size) = empty {
-                    if size > f.length {
-                        data.insert(empty_index + 1, Entry::Free(size - f.length));
-                    }
-                } else {
-                    panic!("Should be free");
+        .rev()
+        .map(|&(file, start)| {
+            let length = file.length as u64;
+            let start = match free.first_fit(length, start) {
+                Some(gap) => {
+                    free.remove(gap.start..gap.start + length);
+                    free.insert(start..start + length);
+                    gap.start
                 }
-                data = merge_free_spaces(data);
-            }
-        }
-    }
-    checksum(&expand(&data))
+                None => start,
+            };
+            (start..start + length).sum::<u64>() * file.id as u64
+        })
+        .sum()
 }
 
 #[cfg(test)]
@@ -0,0 +1,340 @@
+use crate::utils::c2::{C2Field, C2};
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitOr, Not};
+
+const WORD: usize = u64::BITS as usize;
+
+/// Fixed size boolean grid packed into `u64` words, every row starts at a new word.
+#[derive(Default, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+#[allow(dead_code)]
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(WORD);
+        Self {
+            width,
+            height,
+            words_per_row,
+            bits: vec![0; words_per_row * height],
+        }
+    }
+
+    /// Grid with every cell set.
+    pub fn full(width: usize, height: usize) -> Self {
+        !Self::new(width, height)
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    pub fn contains(&self, coord: &C2) -> bool {
+        coord.x >= 0
+            && coord.y >= 0
+            && (coord.x as usize) < self.width
+            && (coord.y as usize) < self.height
+    }
+
+    #[inline(always)]
+    fn position(&self, coord: &C2) -> (usize, u64) {
+        let x = coord.x as usize;
+        let word = coord.y as usize * self.words_per_row + x / WORD;
+        (word, 1 << (x % WORD))
+    }
+
+    /// Cells outside of the grid are never set.
+    #[inline]
+    pub fn get(&self, coord: &C2) -> bool {
+        if !self.contains(coord) {
+            return false;
+        }
+        let (word, mask) = self.position(coord);
+        self.bits[word] & mask != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, coord: &C2, value: bool) {
+        debug_assert!(self.contains(coord));
+        let (word, mask) = self.position(coord);
+        if value {
+            self.bits[word] |= mask;
+        } else {
+            self.bits[word] &= !mask;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|w| *w == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = C2> + '_ {
+        self.bits.iter().enumerate().flat_map(move |(i, word)| {
+            let y = (i / self.words_per_row) as i32;
+            let x0 = (i % self.words_per_row) * WORD;
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    None
+                } else {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(C2::new((x0 + bit) as i32, y))
+                }
+            })
+        })
+    }
+
+    fn zip_with<F>(&self, other: &BitGrid, f: F) -> BitGrid
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grid sizes differ"
+        );
+        BitGrid {
+            bits: self
+                .bits
+                .iter()
+                .zip(other.bits.iter())
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+            ..*self
+        }
+    }
+
+    pub fn union(&self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    pub fn difference(&self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a & !b)
+    }
+
+    /// Words of every row. A zero-width grid has no words, so there is nothing to chunk.
+    fn rows_mut(&mut self) -> std::slice::ChunksMut<'_, u64> {
+        self.bits.chunks_mut(self.words_per_row.max(1))
+    }
+
+    /// Clears padding bits past `width` in the last word of every row.
+    fn mask_rows(&mut self) {
+        let rest = self.width % WORD;
+        if rest == 0 {
+            return;
+        }
+        let mask = (1u64 << rest) - 1;
+        for row in self.rows_mut() {
+            if let Some(last) = row.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+
+    /// Every set cell moved one column to the right, cells falling off the edge are dropped.
+    pub fn shift_right(&self) -> BitGrid {
+        let mut result = self.clone();
+        for row in result.rows_mut() {
+            let mut carry = 0;
+            for word in row.iter_mut() {
+                let next_carry = *word >> (WORD - 1);
+                *word = (*word << 1) | carry;
+                carry = next_carry;
+            }
+        }
+        result.mask_rows();
+        result
+    }
+
+    /// Every set cell moved one column to the left, cells falling off the edge are dropped.
+    pub fn shift_left(&self) -> BitGrid {
+        let mut result = self.clone();
+        for row in result.rows_mut() {
+            let mut carry = 0;
+            for word in row.iter_mut().rev() {
+                let next_carry = *word << (WORD - 1);
+                *word = (*word >> 1) | carry;
+                carry = next_carry;
+            }
+        }
+        result
+    }
+
+    /// Every set cell moved one row up, the top row is dropped.
+    pub fn shift_up(&self) -> BitGrid {
+        let mut result = BitGrid::new(self.width, self.height);
+        if self.height > 1 {
+            let n = self.words_per_row;
+            result.bits[..(self.height - 1) * n].copy_from_slice(&self.bits[n..]);
+        }
+        result
+    }
+
+    /// Every set cell moved one row down, the bottom row is dropped.
+    pub fn shift_down(&self) -> BitGrid {
+        let mut result = BitGrid::new(self.width, self.height);
+        if self.height > 1 {
+            let n = self.words_per_row;
+            result.bits[n..].copy_from_slice(&self.bits[..(self.height - 1) * n]);
+        }
+        result
+    }
+
+    /// Set cells together with their orthogonal neighbors.
+    pub fn expand4(&self) -> BitGrid {
+        self.union(&self.shift_left())
+            .union(&self.shift_right())
+            .union(&self.shift_up())
+            .union(&self.shift_down())
+    }
+
+    /// Cells reachable from `start` moving orthogonally over set cells of `self`.
+    pub fn flood_fill(&self, start: &C2) -> BitGrid {
+        let mut reached = BitGrid::new(self.width, self.height);
+        if !self.get(start) {
+            return reached;
+        }
+        reached.set(start, true);
+        loop {
+            let next = reached.expand4().intersection(self);
+            if next == reached {
+                return reached;
+            }
+            reached = next;
+        }
+    }
+
+    /// Number of orthogonal steps over set cells needed to get from `start` to `end`.
+    ///
+    /// Whole BFS layers are expanded at once.
+    pub fn distance(&self, start: &C2, end: &C2) -> Option<usize> {
+        if !self.get(start) || !self.get(end) {
+            return None;
+        }
+        let mut visited = BitGrid::new(self.width, self.height);
+        visited.set(start, true);
+        let mut frontier = visited.clone();
+        let mut steps = 0;
+        while !frontier.get(end) {
+            frontier = frontier.expand4().intersection(self).difference(&visited);
+            if frontier.is_empty() {
+                return None;
+            }
+            visited = visited.union(&frontier);
+            steps += 1;
+        }
+        Some(steps)
+    }
+}
+
+impl From<&C2Field<bool>> for BitGrid {
+    fn from(field: &C2Field<bool>) -> Self {
+        let mut grid = BitGrid::new(field.width(), field.height());
+        for (c, v) in field.iter() {
+            if *v {
+                grid.set(c, true);
+            }
+        }
+        grid
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+    fn bitor(self, other: &BitGrid) -> BitGrid {
+        self.union(other)
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+    fn bitand(self, other: &BitGrid) -> BitGrid {
+        self.intersection(other)
+    }
+}
+
+impl Not for BitGrid {
+    type Output = BitGrid;
+    fn not(mut self) -> BitGrid {
+        self.bits.iter_mut().for_each(|w| *w = !*w);
+        self.mask_rows();
+        self
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let set = self.get(&C2::new(x as i32, y as i32));
+                write!(f, "{}", if set { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shifts_cross_word_boundaries() {
+        let mut grid = BitGrid::new(130, 3);
+        grid.set(&C2::new(63, 1), true);
+        grid.set(&C2::new(129, 0), true);
+        let right = grid.shift_right();
+        assert_eq!(right.iter().collect::<Vec<_>>(), vec![C2::new(64, 1)]);
+        let left = right.shift_left().shift_left();
+        assert_eq!(left.iter().collect::<Vec<_>>(), vec![C2::new(62, 1)]);
+        assert_eq!(grid.shift_down().iter().count(), 2);
+        assert_eq!(
+            grid.shift_up().iter().collect::<Vec<_>>(),
+            vec![C2::new(63, 0)]
+        );
+        assert_eq!(BitGrid::full(130, 3).count_ones(), 390);
+
+        let empty = BitGrid::full(0, 3);
+        assert_eq!(empty.shift_right().shift_left().count_ones(), 0);
+        assert_eq!(empty.shift_down().shift_up().count_ones(), 0);
+    }
+
+    #[test]
+    fn flood_fill_and_distance() {
+        let field = C2Field::from_string("..#..\n.##..\n.....\n###.#\n", |c| c == '.');
+        let open = BitGrid::from(&field);
+        assert_eq!(open.count_ones(), 13);
+        assert_eq!(open.flood_fill(&C2::ZERO).count_ones(), 13);
+        assert_eq!(open.distance(&C2::ZERO, &C2::new(3, 0)), Some(7));
+        assert_eq!(open.distance(&C2::ZERO, &C2::new(3, 3)), Some(6));
+        assert_eq!(open.distance(&C2::ZERO, &C2::new(2, 0)), None);
+    }
+}
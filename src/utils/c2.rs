@@ -1,5 +1,7 @@
+use crate::utils::num::gcd;
+use fxhash::FxHashMap;
 use std::fmt::{Display, Formatter};
-use std::ops::{Add, Mul, Sub};
+use std::ops::{Add, BitAnd, BitOr, Index, IndexMut, Mul, Not, Sub};
 
 pub static C2_8_NEIGHBORS: [C2; 8] = [
     C2::new(0, -1),
@@ -113,6 +115,315 @@ impl C2 {
     pub fn to_manhattan(self) -> u32 {
         (self.x.abs() + self.y.abs()) as u32
     }
+
+    /// Component-wise Euclidean modulo, result is always within `[0,0]..size`.
+    #[inline]
+    pub fn rem_euclid(self, size: C2) -> C2 {
+        C2::new(self.x.rem_euclid(size.x), self.y.rem_euclid(size.y))
+    }
+
+    /// Position after moving `steps` times by `velocity` on a torus of `size`.
+    ///
+    /// Computed directly in `i64`, so any step count and velocity sign is fine.
+    pub fn step_wrapping(self, velocity: C2, steps: i64, size: C2) -> C2 {
+        let axis = |p: i32, v: i32, m: i32| {
+            let m = m as i64;
+            let p = (p as i64).rem_euclid(m);
+            let v = (v as i64).rem_euclid(m);
+            ((p + v * steps.rem_euclid(m)) % m) as i32
+        };
+        C2::new(
+            axis(self.x, velocity.x, size.x),
+            axis(self.y, velocity.y, size.y),
+        )
+    }
+
+    /// Endless walk starting at `self` (included) and moving by `step`.
+    pub fn ray(self, step: C2) -> impl Iterator<Item = C2> {
+        std::iter::successors(Some(self), move |c| Some(*c + step))
+    }
+
+    /// Walk from `self` (included) by `step` while staying in `min..max`.
+    pub fn ray_within(self, step: C2, min: C2, max: C2) -> impl Iterator<Item = C2> {
+        let (from, to) = line_span(self, step, min, max);
+        let to = if from > 0 { -1 } else { to };
+        (0..=to).map(move |t| self + step * t as i32)
+    }
+
+    /// Smallest lattice step along the line from `self` towards `other`.
+    pub fn lattice_step(self, other: C2) -> C2 {
+        let delta = other - self;
+        let g = gcd(delta.x as i64, delta.y as i64).max(1) as i32;
+        C2::new(delta.x / g, delta.y / g)
+    }
+
+    /// Every lattice point on the infinite line through `self` and `other` inside `min..max`.
+    pub fn lattice_line_within(self, other: C2, min: C2, max: C2) -> impl Iterator<Item = C2> {
+        let step = self.lattice_step(other);
+        let (from, to) = line_span(self, step, min, max);
+        (from..=to).map(move |t| self + step * t as i32)
+    }
+
+    /// Bresenham segment from `self` to `other`, both ends included.
+    pub fn line_to(self, other: C2) -> impl Iterator<Item = C2> {
+        let dx = (other.x - self.x).abs();
+        let dy = -(other.y - self.y).abs();
+        let sx = (other.x - self.x).signum();
+        let sy = (other.y - self.y).signum();
+        let mut error = dx + dy;
+        let mut current = Some(self);
+        std::iter::from_fn(move || {
+            let c = current?;
+            current = if c == other {
+                None
+            } else {
+                let mut next = c;
+                let e2 = 2 * error;
+                if e2 >= dy {
+                    error += dy;
+                    next.x += sx;
+                }
+                if e2 <= dx {
+                    error += dx;
+                    next.y += sy;
+                }
+                Some(next)
+            };
+            Some(c)
+        })
+    }
+}
+
+/// Inclusive range of `t` for which `start + step * t` stays in `min..max`, empty when `from > to`.
+///
+/// A zero `step` only ever yields `start` itself.
+fn line_span(start: C2, step: C2, min: C2, max: C2) -> (i64, i64) {
+    if step == C2::ZERO {
+        let inside = (min.x..max.x).contains(&start.x) && (min.y..max.y).contains(&start.y);
+        return if inside { (0, 0) } else { (1, 0) };
+    }
+    let axis = |a: i32, s: i32, lo: i32, hi: i32| -> (i64, i64) {
+        let (a, s, lo, hi) = (a as i64, s as i64, lo as i64, hi as i64 - 1);
+        match s.signum() {
+            0 if lo <= a && a <= hi => (i64::MIN, i64::MAX),
+            0 => (1, 0),
+            1 => (-(a - lo).div_euclid(s), (hi - a).div_euclid(s)),
+            _ => (-(hi - a).div_euclid(-s), (a - lo).div_euclid(-s)),
+        }
+    };
+    let (x_from, x_to) = axis(start.x, step.x, min.x, max.x);
+    let (y_from, y_to) = axis(start.y, step.y, min.y, max.y);
+    (x_from.max(y_from), x_to.min(y_to))
+}
+
+/// Grid direction, ordered clockwise starting from up.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+#[allow(dead_code)]
+impl Direction {
+    pub const ALL4: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub const ALL8: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    pub fn iter4() -> impl Iterator<Item = Direction> {
+        Self::ALL4.into_iter()
+    }
+
+    pub fn iter8() -> impl Iterator<Item = Direction> {
+        Self::ALL8.into_iter()
+    }
+
+    #[inline]
+    const fn from_index(index: u8) -> Direction {
+        Self::ALL8[(index % 8) as usize]
+    }
+
+    #[inline]
+    pub const fn index(self) -> u8 {
+        self as u8
+    }
+
+    #[inline]
+    pub const fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    #[inline]
+    pub const fn turn_right(self) -> Direction {
+        Self::from_index(self.index() + 2)
+    }
+
+    #[inline]
+    pub const fn turn_left(self) -> Direction {
+        Self::from_index(self.index() + 6)
+    }
+
+    #[inline]
+    pub const fn turn_around(self) -> Direction {
+        Self::from_index(self.index() + 4)
+    }
+
+    pub const fn to_c2(self) -> C2 {
+        match self {
+            Direction::Up => C2_UP,
+            Direction::UpRight => C2::new(1, -1),
+            Direction::Right => C2_RIGHT,
+            Direction::DownRight => C2::new(1, 1),
+            Direction::Down => C2_DOWN,
+            Direction::DownLeft => C2::new(-1, 1),
+            Direction::Left => C2_LEFT,
+            Direction::UpLeft => C2::new(-1, -1),
+        }
+    }
+
+    /// Direction of a unit step, `None` for anything else.
+    pub fn from_c2(c: C2) -> Option<Direction> {
+        Self::ALL8.into_iter().find(|d| d.to_c2() == c)
+    }
+
+    /// `^`, `>`, `v` or `<`, diagonals have no arrow.
+    pub const fn to_arrow(self) -> Option<char> {
+        match self {
+            Direction::Up => Some('^'),
+            Direction::Right => Some('>'),
+            Direction::Down => Some('v'),
+            Direction::Left => Some('<'),
+            _ => None,
+        }
+    }
+
+    pub const fn from_arrow(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Direction::Up),
+            '>' => Some(Direction::Right),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+}
+
+impl From<Direction> for C2 {
+    #[inline]
+    fn from(d: Direction) -> C2 {
+        d.to_c2()
+    }
+}
+
+impl Add<Direction> for C2 {
+    type Output = C2;
+    #[inline]
+    fn add(self, d: Direction) -> C2 {
+        self + d.to_c2()
+    }
+}
+
+/// Set of [`Direction`]s packed into a single byte.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct DirectionSet(u8);
+
+#[allow(dead_code)]
+impl DirectionSet {
+    pub const EMPTY: DirectionSet = DirectionSet(0);
+    pub const ALL4: DirectionSet = DirectionSet(0b0101_0101);
+    pub const ALL8: DirectionSet = DirectionSet(0b1111_1111);
+
+    #[inline]
+    pub const fn of(d: Direction) -> DirectionSet {
+        DirectionSet(1 << d.index())
+    }
+
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn contains(self, d: Direction) -> bool {
+        self.0 & (1 << d.index()) != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, d: Direction) {
+        self.0 |= 1 << d.index();
+    }
+
+    #[inline]
+    pub fn remove(&mut self, d: Direction) {
+        self.0 &= !(1 << d.index());
+    }
+
+    #[inline]
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Direction> {
+        Direction::iter8().filter(move |d| self.contains(*d))
+    }
+}
+
+impl FromIterator<Direction> for DirectionSet {
+    fn from_iter<I: IntoIterator<Item = Direction>>(iter: I) -> Self {
+        let mut set = DirectionSet::EMPTY;
+        for d in iter {
+            set.insert(d);
+        }
+        set
+    }
+}
+
+impl BitOr for DirectionSet {
+    type Output = Self;
+    #[inline]
+    fn bitor(self, other: Self) -> Self {
+        DirectionSet(self.0 | other.0)
+    }
+}
+
+impl BitAnd for DirectionSet {
+    type Output = Self;
+    #[inline]
+    fn bitand(self, other: Self) -> Self {
+        DirectionSet(self.0 & other.0)
+    }
+}
+
+impl Not for DirectionSet {
+    type Output = Self;
+    #[inline]
+    fn not(self) -> Self {
+        DirectionSet(!self.0)
+    }
 }
 
 #[derive(Default, Clone, Eq, PartialEq, Hash)]
@@ -148,6 +459,17 @@ where
         }
     }
 
+    /// Field from values in row-major order.
+    pub fn from_vec(width: usize, height: usize, store: Vec<T>) -> C2Field<T> {
+        assert_eq!(store.len(), width * height, "Store doesn't fit the size");
+        C2Field {
+            width,
+            height,
+            store,
+            indices: Self::indices(width, height),
+        }
+    }
+
     pub fn from_string(input: &str, mapping: fn(char) -> T) -> C2Field<T> {
         let mut store = Vec::with_capacity(input.len());
         let width = input.find("\n").expect("At least one endline");
@@ -221,28 +543,100 @@ where
         C2::new(x as i32, y as i32)
     }
 
+    #[inline]
+    pub fn contains(&self, coord: &C2) -> bool {
+        in_bounds(coord, self.width, self.height)
+    }
+
     pub fn get(&self, coord: &C2) -> Option<&T> {
-        if coord.x < 0
-            || coord.y < 0
-            || coord.y as usize >= self.height
-            || coord.x as usize >= self.width
-        {
+        if self.contains(coord) {
+            Some(&self.store[self.indice(coord)])
+        } else {
             None
+        }
+    }
+
+    pub fn get_mut(&mut self, coord: &C2) -> Option<&mut T> {
+        if self.contains(coord) {
+            let i = self.indice(coord);
+            Some(&mut self.store[i])
         } else {
-            Some(&self.store[self.indice(coord)])
+            None
         }
     }
 
     pub fn set(&mut self, coord: &C2, item: T) {
-        debug_assert!(
-            !(coord.x < 0
-                || coord.y < 0
-                || coord.y as usize >= self.height
-                || coord.x as usize >= self.width)
-        );
+        debug_assert!(self.contains(coord));
         let i = self.indice(coord);
         self.store[i] = item;
     }
+
+    /// Sets `item` at `coord` if it is inside the field, otherwise hands the item back.
+    pub fn try_set(&mut self, coord: &C2, item: T) -> Result<(), T> {
+        if self.contains(coord) {
+            let i = self.indice(coord);
+            self.store[i] = item;
+            Ok(())
+        } else {
+            Err(item)
+        }
+    }
+
+    /// Orthogonal neighbors of `coord` that are inside the field.
+    pub fn neighbors4(&self, coord: &C2) -> impl Iterator<Item = C2> {
+        let (width, height) = (self.width, self.height);
+        coord
+            .neighbors_4()
+            .into_iter()
+            .filter(move |c| in_bounds(c, width, height))
+    }
+
+    /// Orthogonal and diagonal neighbors of `coord` that are inside the field.
+    pub fn neighbors8(&self, coord: &C2) -> impl Iterator<Item = C2> {
+        let (width, height) = (self.width, self.height);
+        let coord = *coord;
+        C2_8_NEIGHBORS
+            .iter()
+            .map(move |d| coord + *d)
+            .filter(move |c| in_bounds(c, width, height))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.store[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.store[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column out of range");
+        self.store.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    #[inline]
+    fn size(&self) -> C2 {
+        C2::new(self.width as i32, self.height as i32)
+    }
+
+    /// Walk from `from` (included) by `step` until leaving the field.
+    pub fn ray(&self, from: &C2, step: &C2) -> impl Iterator<Item = C2> {
+        from.ray_within(*step, C2::ZERO, self.size())
+    }
+
+    /// Every lattice point of the field on the line through `a` and `b`.
+    pub fn lattice_line(&self, a: &C2, b: &C2) -> impl Iterator<Item = C2> {
+        a.lattice_line_within(*b, C2::ZERO, self.size())
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&C2, &T)> {
         self.indices.iter().zip(self.store.iter())
     }
@@ -300,6 +694,111 @@ where
             indices: self.indices.clone(),
         }
     }
+
+    /// Builds a new field described by `transform`, cells that map outside of this field get `fill`.
+    fn remap(&self, transform: &C2Transform, fill: T) -> C2Field<T> {
+        let (width, height) = (transform.width, transform.height);
+        let indices = Self::indices(width, height);
+        let store = indices
+            .iter()
+            .map(|c| {
+                self.get(&transform.original(c))
+                    .cloned()
+                    .unwrap_or_else(|| fill.clone())
+            })
+            .collect();
+
+        C2Field {
+            width,
+            height,
+            store,
+            indices,
+        }
+    }
+
+    /// Applies `transform`, cells without an original counterpart are left as `T::default()`.
+    pub fn transform(&self, transform: &C2Transform) -> C2Field<T> {
+        self.remap(transform, T::default())
+    }
+
+    pub fn rotate_right(&self) -> C2Field<T> {
+        self.transform(&C2Transform::rotate_right(self.width, self.height))
+    }
+
+    pub fn rotate_left(&self) -> C2Field<T> {
+        self.transform(&C2Transform::rotate_left(self.width, self.height))
+    }
+
+    pub fn rotate_180(&self) -> C2Field<T> {
+        self.transform(&C2Transform::rotate_180(self.width, self.height))
+    }
+
+    pub fn transpose(&self) -> C2Field<T> {
+        self.transform(&C2Transform::transpose(self.width, self.height))
+    }
+
+    pub fn flip_horizontal(&self) -> C2Field<T> {
+        self.transform(&C2Transform::flip_horizontal(self.width, self.height))
+    }
+
+    pub fn flip_vertical(&self) -> C2Field<T> {
+        self.transform(&C2Transform::flip_vertical(self.width, self.height))
+    }
+
+    pub fn crop(&self, origin: &C2, width: usize, height: usize) -> C2Field<T> {
+        self.transform(&C2Transform::crop(origin, width, height))
+    }
+
+    pub fn pad(&self, border: usize, fill: T) -> C2Field<T> {
+        self.remap(&C2Transform::pad(self.width, self.height, border), fill)
+    }
+
+    /// Borrowed rectangle of this field, coordinates inside the view start at `[0,0]`.
+    pub fn view(&self, origin: &C2, width: usize, height: usize) -> C2View<'_, T> {
+        assert!(
+            width == 0
+                || height == 0
+                || (self.contains(origin)
+                    && self.contains(&(*origin + C2::new(width as i32 - 1, height as i32 - 1)))),
+            "View does not fit into the field"
+        );
+        C2View {
+            field: self,
+            origin: *origin,
+            width,
+            height,
+        }
+    }
+}
+
+#[inline(always)]
+fn in_bounds(coord: &C2, width: usize, height: usize) -> bool {
+    coord.x >= 0 && coord.y >= 0 && (coord.x as usize) < width && (coord.y as usize) < height
+}
+
+impl<T> Index<C2> for C2Field<T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    type Output = T;
+
+    #[inline]
+    fn index(&self, coord: C2) -> &T {
+        assert!(self.contains(&coord), "{} is outside of the field", coord);
+        &self.store[self.indice(&coord)]
+    }
+}
+
+impl<T> IndexMut<C2> for C2Field<T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    #[inline]
+    fn index_mut(&mut self, coord: C2) -> &mut T {
+        assert!(self.contains(&coord), "{} is outside of the field", coord);
+        let i = self.indice(&coord);
+        &mut self.store[i]
+    }
 }
 
 impl Display for C2Field<char> {
@@ -313,3 +812,711 @@ impl Display for C2Field<char> {
         Ok(())
     }
 }
+
+/// Mapping between coordinates of a transformed field and the field it was built from.
+///
+/// `original = matrix * transformed + offset`, where `matrix` is always a rotation or a
+/// reflection, so the reverse mapping is its transpose.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct C2Transform {
+    matrix: [[i32; 2]; 2],
+    offset: C2,
+    width: usize,
+    height: usize,
+}
+
+#[allow(dead_code)]
+impl C2Transform {
+    const IDENTITY: [[i32; 2]; 2] = [[1, 0], [0, 1]];
+
+    const fn new(matrix: [[i32; 2]; 2], offset: C2, width: usize, height: usize) -> Self {
+        Self {
+            matrix,
+            offset,
+            width,
+            height,
+        }
+    }
+
+    pub const fn identity(width: usize, height: usize) -> Self {
+        Self::new(Self::IDENTITY, C2::ZERO, width, height)
+    }
+
+    /// Clockwise quarter turn.
+    pub const fn rotate_right(width: usize, height: usize) -> Self {
+        Self::new(
+            [[0, 1], [-1, 0]],
+            C2::new(0, height as i32 - 1),
+            height,
+            width,
+        )
+    }
+
+    /// Counter-clockwise quarter turn.
+    pub const fn rotate_left(width: usize, height: usize) -> Self {
+        Self::new(
+            [[0, -1], [1, 0]],
+            C2::new(width as i32 - 1, 0),
+            height,
+            width,
+        )
+    }
+
+    pub const fn rotate_180(width: usize, height: usize) -> Self {
+        Self::new(
+            [[-1, 0], [0, -1]],
+            C2::new(width as i32 - 1, height as i32 - 1),
+            width,
+            height,
+        )
+    }
+
+    pub const fn transpose(width: usize, height: usize) -> Self {
+        Self::new([[0, 1], [1, 0]], C2::ZERO, height, width)
+    }
+
+    /// Mirrors left to right.
+    pub const fn flip_horizontal(width: usize, height: usize) -> Self {
+        Self::new(
+            [[-1, 0], [0, 1]],
+            C2::new(width as i32 - 1, 0),
+            width,
+            height,
+        )
+    }
+
+    /// Mirrors top to bottom.
+    pub const fn flip_vertical(width: usize, height: usize) -> Self {
+        Self::new(
+            [[1, 0], [0, -1]],
+            C2::new(0, height as i32 - 1),
+            width,
+            height,
+        )
+    }
+
+    pub const fn crop(origin: &C2, width: usize, height: usize) -> Self {
+        Self::new(Self::IDENTITY, *origin, width, height)
+    }
+
+    pub const fn pad(width: usize, height: usize, border: usize) -> Self {
+        let b = border as i32;
+        Self::new(
+            Self::IDENTITY,
+            C2::new(-b, -b),
+            width + 2 * border,
+            height + 2 * border,
+        )
+    }
+
+    /// Chains `next`, which has to be built for the dimensions this transform produces.
+    pub fn then(&self, next: &C2Transform) -> Self {
+        let m = &self.matrix;
+        let n = &next.matrix;
+        let matrix = [
+            [
+                m[0][0] * n[0][0] + m[0][1] * n[1][0],
+                m[0][0] * n[0][1] + m[0][1] * n[1][1],
+            ],
+            [
+                m[1][0] * n[0][0] + m[1][1] * n[1][0],
+                m[1][0] * n[0][1] + m[1][1] * n[1][1],
+            ],
+        ];
+        Self::new(
+            matrix,
+            self.apply(&next.offset) + self.offset,
+            next.width,
+            next.height,
+        )
+    }
+
+    #[inline]
+    fn apply(&self, c: &C2) -> C2 {
+        let m = &self.matrix;
+        C2::new(m[0][0] * c.x + m[0][1] * c.y, m[1][0] * c.x + m[1][1] * c.y)
+    }
+
+    /// Coordinate in the source field for coordinate `c` of the transformed field.
+    #[inline]
+    pub fn original(&self, c: &C2) -> C2 {
+        self.apply(c) + self.offset
+    }
+
+    /// Coordinate in the transformed field for coordinate `c` of the source field.
+    #[inline]
+    pub fn transformed(&self, c: &C2) -> C2 {
+        let m = &self.matrix;
+        let d = *c - self.offset;
+        C2::new(m[0][0] * d.x + m[1][0] * d.y, m[0][1] * d.x + m[1][1] * d.y)
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Borrowed rectangular part of a [`C2Field`].
+#[derive(Copy, Clone)]
+pub struct C2View<'a, T> {
+    field: &'a C2Field<T>,
+    origin: C2,
+    width: usize,
+    height: usize,
+}
+
+#[allow(dead_code)]
+impl<'a, T> C2View<'a, T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    pub fn contains(&self, coord: &C2) -> bool {
+        in_bounds(coord, self.width, self.height)
+    }
+
+    #[inline]
+    pub fn original(&self, coord: &C2) -> C2 {
+        *coord + self.origin
+    }
+
+    #[inline]
+    pub fn local(&self, coord: &C2) -> C2 {
+        *coord - self.origin
+    }
+
+    pub fn get(&self, coord: &C2) -> Option<&'a T> {
+        if self.contains(coord) {
+            self.field.get(&self.original(coord))
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &'a [T] {
+        let from = self.field.indice(&self.original(&C2::new(0, y as i32)));
+        &self.field.store[from..from + self.width]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (C2, &'a T)> + '_ {
+        (0..self.height).flat_map(move |y| {
+            self.row(y)
+                .iter()
+                .enumerate()
+                .map(move |(x, v)| (C2::new(x as i32, y as i32), v))
+        })
+    }
+
+    pub fn to_field(&self) -> C2Field<T> {
+        self.field.crop(&self.origin, self.width, self.height)
+    }
+}
+
+/// Unbounded grid that only stores occupied cells, coordinates may be negative.
+#[derive(Default, Clone, Eq, PartialEq, Debug)]
+pub struct SparseGrid<T> {
+    store: FxHashMap<C2, T>,
+}
+
+#[allow(dead_code)]
+impl<T> SparseGrid<T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    pub fn new() -> Self {
+        Self {
+            store: FxHashMap::default(),
+        }
+    }
+
+    /// Parses a text grid, characters mapped to `None` are left empty.
+    pub fn from_string(input: &str, mapping: fn(char) -> Option<T>) -> SparseGrid<T> {
+        Self::from_string_indexed(input, &mut |_, ch| mapping(ch))
+    }
+
+    pub fn from_string_indexed<F>(input: &str, mapping: &mut F) -> SparseGrid<T>
+    where
+        F: FnMut(C2, char) -> Option<T>,
+    {
+        let mut grid = Self::new();
+        for (y, line) in input.lines().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                let coord = C2::new(x as i32, y as i32);
+                if let Some(item) = mapping(coord, ch) {
+                    grid.set(&coord, item);
+                }
+            }
+        }
+        grid
+    }
+
+    #[inline]
+    pub fn contains(&self, coord: &C2) -> bool {
+        self.store.contains_key(coord)
+    }
+
+    #[inline]
+    pub fn get(&self, coord: &C2) -> Option<&T> {
+        self.store.get(coord)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, coord: &C2) -> Option<&mut T> {
+        self.store.get_mut(coord)
+    }
+
+    /// Sets `item` at `coord`, returning the previous value.
+    #[inline]
+    pub fn set(&mut self, coord: &C2, item: T) -> Option<T> {
+        self.store.insert(*coord, item)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, coord: &C2) -> Option<T> {
+        self.store.remove(coord)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&C2, &T)> {
+        self.store.iter()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &C2> {
+        self.store.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.store.values()
+    }
+
+    /// Top-left and bottom-right corners (both inclusive) of occupied cells.
+    pub fn bounds(&self) -> Option<(C2, C2)> {
+        let mut keys = self.store.keys();
+        let first = *keys.next()?;
+        Some(keys.fold((first, first), |(min, max), c| {
+            (
+                C2::new(min.x.min(c.x), min.y.min(c.y)),
+                C2::new(max.x.max(c.x), max.y.max(c.y)),
+            )
+        }))
+    }
+
+    pub fn find_first(&self, value: T) -> Option<C2> {
+        self.store
+            .iter()
+            .filter(|(_, v)| **v == value)
+            .map(|(c, _)| *c)
+            .min_by_key(|c| (c.y, c.x))
+    }
+
+    /// Dense copy covering the bounding box, empty cells are `T::default()`.
+    ///
+    /// Returned transform maps field coordinates back to grid coordinates.
+    pub fn to_field(&self) -> (C2Field<T>, C2Transform) {
+        let (min, max) = self.bounds().unwrap_or((C2::ZERO, C2::new(-1, -1)));
+        let transform = C2Transform::crop(
+            &min,
+            (max.x - min.x + 1) as usize,
+            (max.y - min.y + 1) as usize,
+        );
+        let mut field = C2Field::new(transform.width(), transform.height());
+        for (c, v) in self.store.iter() {
+            field.set(&transform.transformed(c), v.clone());
+        }
+        (field, transform)
+    }
+
+    pub fn render<F>(&self, empty: char, mut f: F) -> String
+    where
+        F: FnMut(&T) -> char,
+    {
+        let mut out = String::new();
+        if let Some((min, max)) = self.bounds() {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    out.push(self.get(&C2::new(x, y)).map_or(empty, &mut f));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    pub fn debug_print<F>(&self, f: F)
+    where
+        F: FnMut(&T) -> char,
+    {
+        println!("{}", self.render(' ', f));
+        println!();
+    }
+}
+
+impl<T> From<&C2Field<T>> for SparseGrid<T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    fn from(field: &C2Field<T>) -> Self {
+        Self {
+            store: field.iter().map(|(c, v)| (*c, v.clone())).collect(),
+        }
+    }
+}
+
+impl Display for SparseGrid<char> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(' ', |c| *c))
+    }
+}
+
+/// Fixed size grid where every coordinate wraps around both edges.
+#[derive(Default, Clone, Eq, PartialEq, Hash)]
+pub struct WrappingField<T> {
+    field: C2Field<T>,
+}
+
+#[allow(dead_code)]
+impl<T> WrappingField<T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            field: C2Field::new(width, height),
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> C2 {
+        C2::new(self.field.width as i32, self.field.height as i32)
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.field.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.field.height
+    }
+
+    /// Canonical coordinate of `coord` inside the underlying field.
+    #[inline]
+    pub fn wrap(&self, coord: &C2) -> C2 {
+        coord.rem_euclid(self.size())
+    }
+
+    /// Position at time `steps` of something starting at `coord` and moving by `velocity`.
+    #[inline]
+    pub fn position_at(&self, coord: &C2, velocity: &C2, steps: i64) -> C2 {
+        coord.step_wrapping(*velocity, steps, self.size())
+    }
+
+    #[inline]
+    pub fn get(&self, coord: &C2) -> &T {
+        &self.field[self.wrap(coord)]
+    }
+
+    #[inline]
+    pub fn set(&mut self, coord: &C2, item: T) {
+        let c = self.wrap(coord);
+        self.field[c] = item;
+    }
+
+    pub fn neighbors4(&self, coord: &C2) -> [C2; 4] {
+        coord.neighbors_4().map(|c| self.wrap(&c))
+    }
+
+    pub fn neighbors8(&self, coord: &C2) -> [C2; 8] {
+        C2_8_NEIGHBORS.map(|d| self.wrap(&(*coord + d)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&C2, &T)> {
+        self.field.iter()
+    }
+
+    #[inline]
+    pub fn field(&self) -> &C2Field<T> {
+        &self.field
+    }
+
+    pub fn into_field(self) -> C2Field<T> {
+        self.field
+    }
+}
+
+impl<T> From<C2Field<T>> for WrappingField<T> {
+    fn from(field: C2Field<T>) -> Self {
+        Self { field }
+    }
+}
+
+impl<T> Index<C2> for WrappingField<T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    type Output = T;
+
+    #[inline]
+    fn index(&self, coord: C2) -> &T {
+        self.get(&coord)
+    }
+}
+
+impl<T> IndexMut<C2> for WrappingField<T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    #[inline]
+    fn index_mut(&mut self, coord: C2) -> &mut T {
+        let c = self.wrap(&coord);
+        &mut self.field[c]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELD: &str = "abc\ndef\n";
+
+    #[test]
+    fn index_and_bounds() {
+        let mut field = C2Field::from_string(FIELD, |c| c);
+        assert_eq!(field[C2::new(1, 1)], 'e');
+        field[C2::new(1, 1)] = 'x';
+        assert_eq!(field.get(&C2::new(1, 1)), Some(&'x'));
+        assert!(!field.contains(&C2::new(3, 0)));
+        assert_eq!(field.try_set(&C2::new(-1, 0), 'z'), Err('z'));
+        assert_eq!(field.try_set(&C2::new(2, 1), 'z'), Ok(()));
+    }
+
+    #[test]
+    fn neighbors_skip_outside() {
+        let field = C2Field::from_string(FIELD, |c| c);
+        assert_eq!(field.neighbors4(&C2::ZERO).count(), 2);
+        assert_eq!(field.neighbors8(&C2::ZERO).count(), 3);
+        assert_eq!(field.neighbors8(&C2::new(1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let field = C2Field::from_string(FIELD, |c| c);
+        assert_eq!(field.row(1), &['d', 'e', 'f']);
+        assert_eq!(field.column(2).collect::<String>(), "cf");
+        assert_eq!(field.rows().count(), 2);
+        assert_eq!(field.columns().count(), 3);
+
+        let empty: C2Field<char> = C2Field::new(0, 2);
+        assert_eq!(empty.rows().collect::<Vec<_>>(), vec![&[] as &[char]; 2]);
+        assert_eq!(empty.columns().count(), 0);
+    }
+
+    #[test]
+    fn rotations_keep_mapping() {
+        let field = C2Field::from_string(FIELD, |c| c);
+        let rotated = field.rotate_right();
+        assert_eq!(rotated.to_string(), "da\neb\nfc\n");
+        assert_eq!(field.rotate_left().to_string(), "cf\nbe\nad\n");
+        assert_eq!(field.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(field.rotate_180().to_string(), "fed\ncba\n");
+        assert_eq!(field.flip_horizontal().to_string(), "cba\nfed\n");
+        assert_eq!(field.flip_vertical().to_string(), "def\nabc\n");
+
+        let t = C2Transform::rotate_right(field.width(), field.height());
+        for (c, v) in rotated.iter() {
+            assert_eq!(field[t.original(c)], *v);
+            assert_eq!(t.transformed(&t.original(c)), *c);
+        }
+
+        let back = t.then(&C2Transform::rotate_left(t.width(), t.height()));
+        assert_eq!(back, C2Transform::identity(3, 2));
+    }
+
+    #[test]
+    fn crop_pad_and_view() {
+        let field = C2Field::from_string(FIELD, |c| c);
+        assert_eq!(
+            field.pad(1, '#').to_string(),
+            "#####\n#abc#\n#def#\n#####\n"
+        );
+        assert_eq!(field.crop(&C2::new(1, 0), 2, 2).to_string(), "bc\nef\n");
+
+        let view = field.view(&C2::new(1, 1), 2, 1);
+        assert_eq!(view.get(&C2::ZERO), Some(&'e'));
+        assert_eq!(view.get(&C2::new(0, 1)), None);
+        assert_eq!(view.original(&C2::new(1, 0)), C2::new(2, 1));
+        assert_eq!(view.iter().map(|(_, v)| *v).collect::<String>(), "ef");
+        assert_eq!(view.to_field().to_string(), "ef\n");
+    }
+
+    #[test]
+    fn sparse_grid_round_trip() {
+        let mut grid = SparseGrid::from_string(".#\n#.\n", |c| (c == '#').then_some(c));
+        assert_eq!(grid.len(), 2);
+        grid.set(&C2::new(-2, -1), '@');
+        assert_eq!(grid.bounds(), Some((C2::new(-2, -1), C2::new(1, 1))));
+        assert_eq!(grid.to_string(), "@   \n   #\n  # \n");
+
+        let (field, transform) = grid.to_field();
+        assert_eq!(field.width(), 4);
+        assert_eq!(field[transform.transformed(&C2::new(-2, -1))], '@');
+        assert_eq!(transform.original(&C2::new(3, 1)), C2::new(1, 0));
+
+        let dense = SparseGrid::from(&C2Field::from_string(FIELD, |c| c));
+        assert_eq!(dense.get(&C2::new(2, 1)), Some(&'f'));
+        assert_eq!(dense.find_first('e'), Some(C2::new(1, 1)));
+    }
+
+    #[test]
+    fn wrapping_positions() {
+        let size = C2::new(11, 7);
+        assert_eq!(C2::new(-1, 8).rem_euclid(size), C2::new(10, 1));
+        let p = C2::new(2, 4);
+        let v = C2::new(2, -3);
+        assert_eq!(p.step_wrapping(v, 5, size), C2::new(1, 3));
+        assert_eq!(
+            p.step_wrapping(C2::new(-1000, 1000), 1_000_000_007, size),
+            {
+                let mut c = p;
+                for _ in 0..(1_000_000_007_i64 % 77) {
+                    c = (c + C2::new(-1000, 1000)).rem_euclid(size);
+                }
+                c
+            }
+        );
+        assert_eq!(
+            p.step_wrapping(v, -5, size),
+            p.step_wrapping(v * -1, 5, size)
+        );
+    }
+
+    #[test]
+    fn wrapping_field() {
+        let mut field: WrappingField<char> = C2Field::from_string(FIELD, |c| c).into();
+        assert_eq!(field[C2::new(-1, -1)], 'f');
+        field.set(&C2::new(3, 2), 'x');
+        assert_eq!(field.field()[C2::ZERO], 'x');
+        assert!(field.neighbors8(&C2::ZERO).contains(&C2::new(2, 1)));
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::UpLeft.turn_around(), Direction::DownRight);
+        assert_eq!(Direction::from_arrow('v').map(C2::from), Some(C2_DOWN));
+        assert_eq!(Direction::Left.to_arrow(), Some('<'));
+        assert_eq!(
+            Direction::from_c2(C2::new(-1, 1)),
+            Some(Direction::DownLeft)
+        );
+        assert_eq!(Direction::from_c2(C2::new(2, 0)), None);
+        for d in Direction::iter8() {
+            assert_eq!(d.to_c2().rotate_right(), d.turn_right().to_c2());
+        }
+        assert_eq!(
+            Direction::iter8().map(C2::from).collect::<Vec<_>>(),
+            C2_8_NEIGHBORS.to_vec()
+        );
+    }
+
+    #[test]
+    fn direction_sets() {
+        let mut set: DirectionSet = [Direction::Up, Direction::Left].into_iter().collect();
+        assert!(set.contains(Direction::Up));
+        assert!(!set.contains(Direction::Down));
+        set.insert(Direction::Down);
+        set.remove(Direction::Up);
+        assert_eq!(set.len(), 2);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Direction::Down, Direction::Left]
+        );
+        assert_eq!(
+            !set & DirectionSet::ALL4,
+            DirectionSet::of(Direction::Up) | DirectionSet::of(Direction::Right)
+        );
+        assert_eq!(
+            DirectionSet::ALL4.iter().collect::<Vec<_>>(),
+            Direction::ALL4.to_vec()
+        );
+    }
+
+    #[test]
+    fn rays_and_lines() {
+        let c = C2::new(1, 1);
+        assert_eq!(
+            c.ray(C2::new(2, -1)).take(3).collect::<Vec<_>>(),
+            vec![c, C2::new(3, 0), C2::new(5, -1)]
+        );
+        let max = C2::new(5, 4);
+        assert_eq!(c.ray_within(C2_RIGHT, C2::ZERO, max).count(), 4);
+        assert_eq!(c.ray_within(C2::new(-1, -1), C2::ZERO, max).count(), 2);
+        assert_eq!(C2::new(7, 1).ray_within(C2_LEFT, C2::ZERO, max).count(), 0);
+        assert_eq!(c.ray_within(C2::ZERO, C2::ZERO, max).count(), 1);
+
+        assert_eq!(C2::new(0, 0).lattice_step(C2::new(4, -6)), C2::new(2, -3));
+        assert_eq!(
+            C2::new(2, 2)
+                .lattice_line_within(C2::new(4, 4), C2::ZERO, max)
+                .collect::<Vec<_>>(),
+            vec![C2::new(0, 0), C2::new(1, 1), C2::new(2, 2), C2::new(3, 3)]
+        );
+        assert_eq!(
+            C2::new(-3, 0)
+                .lattice_line_within(C2::new(-1, 1), C2::ZERO, max)
+                .collect::<Vec<_>>(),
+            vec![C2::new(1, 2), C2::new(3, 3)]
+        );
+
+        let field = C2Field::from_string(FIELD, |c| c);
+        assert_eq!(field.ray(&C2::new(2, 0), &C2_LEFT).count(), 3);
+        assert_eq!(field.lattice_line(&C2::ZERO, &C2::new(1, 1)).count(), 2);
+    }
+
+    #[test]
+    fn bresenham() {
+        assert_eq!(
+            C2::new(0, 0).line_to(C2::new(4, 2)).collect::<Vec<_>>(),
+            vec![
+                C2::new(0, 0),
+                C2::new(1, 1),
+                C2::new(2, 1),
+                C2::new(3, 2),
+                C2::new(4, 2)
+            ]
+        );
+        assert_eq!(C2::new(3, 3).line_to(C2::new(3, 3)).count(), 1);
+        assert_eq!(C2::new(0, 5).line_to(C2::new(0, 0)).count(), 6);
+        assert_eq!(
+            C2::new(2, 2).line_to(C2::new(-1, -1)).last(),
+            Some(C2::new(-1, -1))
+        );
+    }
+}
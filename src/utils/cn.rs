@@ -0,0 +1,353 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+
+/// N-dimensional integer coordinate, the generalisation of [`crate::utils::c2::C2`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+pub struct CN<const N: usize> {
+    pub coords: [i32; N],
+}
+
+#[allow(dead_code)]
+pub type C3 = CN<3>;
+#[allow(dead_code)]
+pub type C4 = CN<4>;
+
+#[allow(dead_code)]
+impl<const N: usize> CN<N> {
+    pub const ZERO: CN<N> = CN { coords: [0; N] };
+
+    pub const fn new(coords: [i32; N]) -> Self {
+        Self { coords }
+    }
+
+    /// Unit vector along `axis`.
+    pub fn unit(axis: usize) -> Self {
+        let mut coords = [0; N];
+        coords[axis] = 1;
+        Self { coords }
+    }
+
+    #[inline]
+    pub fn to_manhattan(self) -> u32 {
+        self.coords.iter().map(|c| c.unsigned_abs()).sum()
+    }
+
+    /// All `3^N - 1` offsets where every component is in `-1..=1`, diagonals included.
+    pub fn neighbor_offsets() -> impl Iterator<Item = CN<N>> {
+        (0..3usize.pow(N as u32))
+            .map(|mut i| {
+                let mut coords = [0; N];
+                for c in coords.iter_mut() {
+                    *c = (i % 3) as i32 - 1;
+                    i /= 3;
+                }
+                CN { coords }
+            })
+            .filter(|c| *c != Self::ZERO)
+    }
+
+    /// All `2N` offsets of length one along a single axis.
+    pub fn orthogonal_offsets() -> impl Iterator<Item = CN<N>> {
+        (0..N).flat_map(|axis| [Self::unit(axis) * -1, Self::unit(axis)])
+    }
+
+    pub fn neighbors(&self) -> impl Iterator<Item = CN<N>> {
+        let c = *self;
+        Self::neighbor_offsets().map(move |d| c + d)
+    }
+
+    pub fn neighbors_orthogonal(&self) -> impl Iterator<Item = CN<N>> {
+        let c = *self;
+        Self::orthogonal_offsets().map(move |d| c + d)
+    }
+}
+
+#[allow(dead_code)]
+impl CN<3> {
+    pub const fn xyz(x: i32, y: i32, z: i32) -> Self {
+        Self { coords: [x, y, z] }
+    }
+}
+
+#[allow(dead_code)]
+impl CN<4> {
+    pub const fn xyzw(x: i32, y: i32, z: i32, w: i32) -> Self {
+        Self {
+            coords: [x, y, z, w],
+        }
+    }
+}
+
+impl<const N: usize> From<[i32; N]> for CN<N> {
+    fn from(coords: [i32; N]) -> Self {
+        Self { coords }
+    }
+}
+
+impl<const N: usize> Index<usize> for CN<N> {
+    type Output = i32;
+    #[inline]
+    fn index(&self, axis: usize) -> &i32 {
+        &self.coords[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for CN<N> {
+    #[inline]
+    fn index_mut(&mut self, axis: usize) -> &mut i32 {
+        &mut self.coords[axis]
+    }
+}
+
+impl<const N: usize> Display for CN<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (i, c) in self.coords.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<const N: usize> Add for CN<N> {
+    type Output = Self;
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self {
+            coords: std::array::from_fn(|i| self.coords[i] + other.coords[i]),
+        }
+    }
+}
+
+impl<const N: usize> Sub for CN<N> {
+    type Output = Self;
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self {
+            coords: std::array::from_fn(|i| self.coords[i] - other.coords[i]),
+        }
+    }
+}
+
+impl<const N: usize> Mul<i32> for CN<N> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, scalar: i32) -> Self {
+        Self {
+            coords: self.coords.map(|c| c * scalar),
+        }
+    }
+}
+
+impl<const N: usize> Mul<CN<N>> for i32 {
+    type Output = CN<N>;
+    #[inline]
+    fn mul(self, c: CN<N>) -> CN<N> {
+        c * self
+    }
+}
+
+/// Dense N-dimensional field anchored at the origin, the generalisation of
+/// [`crate::utils::c2::C2Field`]. The first axis changes fastest in storage.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct CNField<const N: usize, T> {
+    dims: [usize; N],
+    store: Vec<T>,
+    indices: Vec<CN<N>>,
+}
+
+#[allow(dead_code)]
+impl<const N: usize, T> CNField<N, T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    fn indices(dims: [usize; N]) -> Vec<CN<N>> {
+        let len = dims.iter().product();
+        (0..len)
+            .map(|mut i| {
+                let mut coords = [0; N];
+                for (c, d) in coords.iter_mut().zip(dims.iter()) {
+                    *c = (i % d) as i32;
+                    i /= d;
+                }
+                CN { coords }
+            })
+            .collect()
+    }
+
+    pub fn new(dims: [usize; N]) -> Self {
+        let indices = Self::indices(dims);
+        Self {
+            dims,
+            store: vec![T::default(); indices.len()],
+            indices,
+        }
+    }
+
+    #[inline]
+    pub fn dims(&self) -> [usize; N] {
+        self.dims
+    }
+
+    #[inline]
+    pub fn contains(&self, coord: &CN<N>) -> bool {
+        coord
+            .coords
+            .iter()
+            .zip(self.dims.iter())
+            .all(|(c, d)| *c >= 0 && (*c as usize) < *d)
+    }
+
+    #[inline]
+    pub fn indice(&self, coord: &CN<N>) -> usize {
+        coord
+            .coords
+            .iter()
+            .zip(self.dims.iter())
+            .rev()
+            .fold(0, |acc, (c, d)| acc * d + *c as usize)
+    }
+
+    #[inline]
+    pub fn coord(&self, indice: usize) -> CN<N> {
+        self.indices[indice]
+    }
+
+    pub fn get(&self, coord: &CN<N>) -> Option<&T> {
+        if self.contains(coord) {
+            Some(&self.store[self.indice(coord)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, coord: &CN<N>) -> Option<&mut T> {
+        if self.contains(coord) {
+            let i = self.indice(coord);
+            Some(&mut self.store[i])
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, coord: &CN<N>, item: T) {
+        debug_assert!(self.contains(coord));
+        let i = self.indice(coord);
+        self.store[i] = item;
+    }
+
+    pub fn try_set(&mut self, coord: &CN<N>, item: T) -> Result<(), T> {
+        if self.contains(coord) {
+            let i = self.indice(coord);
+            self.store[i] = item;
+            Ok(())
+        } else {
+            Err(item)
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&CN<N>, &T)> {
+        self.indices.iter().zip(self.store.iter())
+    }
+
+    #[inline]
+    pub fn keys(&self) -> &Vec<CN<N>> {
+        &self.indices
+    }
+
+    #[inline]
+    pub fn values(&self) -> &Vec<T> {
+        &self.store
+    }
+
+    /// Neighbors of `coord`, diagonals included, that are inside the field.
+    pub fn neighbors(&self, coord: &CN<N>) -> impl Iterator<Item = CN<N>> + '_ {
+        coord.neighbors().filter(|c| self.contains(c))
+    }
+
+    pub fn neighbors_orthogonal(&self, coord: &CN<N>) -> impl Iterator<Item = CN<N>> + '_ {
+        coord.neighbors_orthogonal().filter(|c| self.contains(c))
+    }
+
+    pub fn find_first(&self, value: T) -> Option<CN<N>> {
+        self.store
+            .iter()
+            .position(|v| *v == value)
+            .map(|p| self.coord(p))
+    }
+
+    pub fn map<F, T2>(&self, mut f: F) -> CNField<N, T2>
+    where
+        F: FnMut(&CN<N>, &T) -> T2,
+    {
+        CNField {
+            dims: self.dims,
+            store: self.iter().map(|(c, v)| f(c, v)).collect(),
+            indices: self.indices.clone(),
+        }
+    }
+}
+
+impl<const N: usize, T> Index<CN<N>> for CNField<N, T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    type Output = T;
+
+    #[inline]
+    fn index(&self, coord: CN<N>) -> &T {
+        assert!(self.contains(&coord), "{} is outside of the field", coord);
+        &self.store[self.indice(&coord)]
+    }
+}
+
+impl<const N: usize, T> IndexMut<CN<N>> for CNField<N, T>
+where
+    T: Clone + Default + PartialEq + Eq,
+{
+    #[inline]
+    fn index_mut(&mut self, coord: CN<N>) -> &mut T {
+        assert!(self.contains(&coord), "{} is outside of the field", coord);
+        let i = self.indice(&coord);
+        &mut self.store[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_neighbors() {
+        let a = C3::xyz(1, -2, 3);
+        let b = C3::new([2, 2, 2]);
+        assert_eq!(a + b, C3::xyz(3, 0, 5));
+        assert_eq!(a - b, C3::xyz(-1, -4, 1));
+        assert_eq!(2 * a, a * 2);
+        assert_eq!(a.to_manhattan(), 6);
+        assert_eq!(a.neighbors().count(), 26);
+        assert_eq!(a.neighbors_orthogonal().count(), 6);
+        assert_eq!(C4::ZERO.neighbors().count(), 80);
+        assert!(C4::xyzw(0, 0, 0, 1).neighbors().any(|c| c == C4::ZERO));
+        assert_eq!(a.to_string(), "[1,-2,3]");
+    }
+
+    #[test]
+    fn field_get_set() {
+        let mut field: CNField<3, u8> = CNField::new([2, 3, 4]);
+        assert_eq!(field.keys().len(), 24);
+        let c = C3::xyz(1, 2, 3);
+        field.set(&c, 7);
+        assert_eq!(field[c], 7);
+        assert_eq!(field.find_first(7), Some(c));
+        assert_eq!(field.coord(field.indice(&c)), c);
+        assert_eq!(field.get(&C3::xyz(2, 0, 0)), None);
+        assert_eq!(field.try_set(&C3::xyz(0, 0, -1), 1), Err(1));
+        assert_eq!(field.neighbors(&C3::ZERO).count(), 7);
+        assert_eq!(field.neighbors_orthogonal(&c).count(), 3);
+        assert_eq!(field.map(|_, v| *v as u32 * 2)[c], 14);
+    }
+}
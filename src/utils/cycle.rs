@@ -0,0 +1,182 @@
+use fxhash::FxHashMap;
+use std::hash::Hash;
+
+/// Shape of the sequence `x0, f(x0), f(f(x0)), ...` of a deterministic state machine:
+/// states from index `start` repeat every `length` steps.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Smallest step index that reaches the same state as step `n`.
+    pub fn reduce(&self, n: u64) -> usize {
+        let start = self.start as u64;
+        if n < start {
+            n as usize
+        } else {
+            (start + (n - start) % self.length as u64) as usize
+        }
+    }
+}
+
+#[inline]
+fn advance<S, F>(mut state: S, step: &mut F, n: usize) -> S
+where
+    F: FnMut(&S) -> S,
+{
+    for _ in 0..n {
+        state = step(&state);
+    }
+    state
+}
+
+/// Floyd's tortoise and hare, keeps only two states in memory.
+#[allow(dead_code)]
+pub fn floyd<S, F>(initial: &S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut tortoise = step(initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let half = step(&hare);
+        hare = step(&half);
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Brent's algorithm, same result as [`floyd`] with fewer calls to `step`.
+pub fn brent<S, F>(initial: &S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = advance(initial.clone(), &mut step, length);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Remembers every state, so it works for machines that may stop (`step` returns `None`).
+///
+/// Returns the visited states in order and the cycle, if the machine did not stop.
+pub fn find_cycle_hashed<S, F>(initial: S, mut step: F) -> (Vec<S>, Option<Cycle>)
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> Option<S>,
+{
+    let mut seen: FxHashMap<S, usize> = FxHashMap::default();
+    let mut history = Vec::new();
+    let mut state = Some(initial);
+    while let Some(current) = state {
+        if let Some(&start) = seen.get(&current) {
+            let length = history.len() - start;
+            return (history, Some(Cycle { start, length }));
+        }
+        seen.insert(current.clone(), history.len());
+        state = step(&current);
+        history.push(current);
+    }
+    (history, None)
+}
+
+/// State after `n` steps, found by detecting the cycle first instead of stepping `n` times.
+#[allow(dead_code)]
+pub fn state_at<S, F>(initial: &S, mut step: F, n: u64) -> S
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let cycle = brent(initial, &mut step);
+    advance(initial.clone(), &mut step, cycle.reduce(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 7 -> 3 -> ...
+    fn step(x: &u32) -> u32 {
+        if *x == 7 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    const EXPECTED: Cycle = Cycle {
+        start: 3,
+        length: 5,
+    };
+
+    #[test]
+    fn detectors_agree() {
+        assert_eq!(floyd(&0, step), EXPECTED);
+        assert_eq!(brent(&0, step), EXPECTED);
+        let (history, cycle) = find_cycle_hashed(0, |x| Some(step(x)));
+        assert_eq!(cycle, Some(EXPECTED));
+        assert_eq!(history.len(), 8);
+        assert_eq!(brent(&5, step).start, 0);
+    }
+
+    #[test]
+    fn jump_to_step() {
+        assert_eq!(EXPECTED.reduce(2), 2);
+        assert_eq!(EXPECTED.reduce(13), 3);
+        assert_eq!(state_at(&0, step, 1_000_000_000_000), 5);
+        assert_eq!(state_at(&0u64, |x| (x * x + 1) % 255, 1_000_000), {
+            let mut x = 0u64;
+            for _ in 0..1_000_000 {
+                x = (x * x + 1) % 255;
+            }
+            x
+        });
+    }
+
+    #[test]
+    fn stopping_machine() {
+        let (history, cycle) = find_cycle_hashed(0, |x| (*x < 4).then_some(x + 1));
+        assert_eq!(history, vec![0, 1, 2, 3, 4]);
+        assert_eq!(cycle, None);
+    }
+}
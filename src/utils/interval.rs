@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::ops::{Range, Sub};
+
+/// Sorted set of disjoint half-open ranges. Touching or overlapping ranges are merged on insert.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntervalSet<T> {
+    // start -> end
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Sub<Output = T>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let mut start = range.start;
+        let mut end = range.end;
+        if let Some((&s, &e)) = self.ranges.range(..=start).next_back() {
+            if e >= start {
+                start = s;
+                end = end.max(e);
+            }
+        }
+        let absorbed: Vec<(T, T)> = self
+            .ranges
+            .range(start..=end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in absorbed {
+            self.ranges.remove(&s);
+            end = end.max(e);
+        }
+        self.ranges.insert(start, end);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        if let Some((&s, &e)) = self.ranges.range(..range.start).next_back() {
+            if e > range.start {
+                self.ranges.insert(s, range.start);
+                if e > range.end {
+                    self.ranges.insert(range.end, e);
+                }
+            }
+        }
+        let cut: Vec<(T, T)> = self
+            .ranges
+            .range(range.start..range.end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in cut {
+            self.ranges.remove(&s);
+            if e > range.end {
+                self.ranges.insert(range.end, e);
+            }
+        }
+    }
+
+    /// Union with `other`.
+    pub fn merge(&mut self, other: &IntervalSet<T>) {
+        for range in other.iter() {
+            self.insert(range);
+        }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, &e)| e > value)
+    }
+
+    /// Range containing `value`.
+    pub fn range_of(&self, value: T) -> Option<Range<T>> {
+        self.ranges
+            .range(..=value)
+            .next_back()
+            .filter(|(_, &e)| e > value)
+            .map(|(&s, &e)| s..e)
+    }
+
+    /// Leftmost range that is at least `length` long and ends no later than `bound`, the
+    /// first-fit rule of allocators.
+    pub fn first_fit(&self, length: T, bound: T) -> Option<Range<T>> {
+        self.ranges
+            .range(..bound)
+            .map(|(&s, &e)| s..e.min(bound))
+            .find(|r| r.end - r.start >= length)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.ranges.iter().map(|(&s, &e)| s..e)
+    }
+
+    /// Number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl<T> FromIterator<Range<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Sub<Output = T>,
+{
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(set: &IntervalSet<i32>) -> Vec<Range<i32>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn insert_merges() {
+        let mut set: IntervalSet<i32> = [5..7, 0..2, 10..12].into_iter().collect();
+        assert_eq!(ranges(&set), vec![0..2, 5..7, 10..12]);
+        set.insert(2..3);
+        set.insert(6..10);
+        assert_eq!(ranges(&set), vec![0..3, 5..12]);
+        set.insert(-5..20);
+        assert_eq!(ranges(&set), vec![-5..20]);
+        set.insert(4..4);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn remove_splits() {
+        let mut set: IntervalSet<i32> = [0..10, 20..30].into_iter().collect();
+        set.remove(3..5);
+        assert_eq!(ranges(&set), vec![0..3, 5..10, 20..30]);
+        set.remove(8..25);
+        assert_eq!(ranges(&set), vec![0..3, 5..8, 25..30]);
+        set.remove(-10..100);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn lookups() {
+        let mut set: IntervalSet<i32> = [0..2, 5..9, 12..20].into_iter().collect();
+        assert!(set.contains(5));
+        assert!(!set.contains(9));
+        assert_eq!(set.range_of(7), Some(5..9));
+        assert_eq!(set.first_fit(3, 100), Some(5..9));
+        assert_eq!(set.first_fit(3, 7), None);
+        assert_eq!(set.first_fit(5, 100), Some(12..20));
+        assert_eq!(set.first_fit(5, 15), None);
+        set.merge(&[2..4, 4..5].into_iter().collect());
+        assert_eq!(ranges(&set), vec![0..9, 12..20]);
+    }
+}
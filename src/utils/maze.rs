@@ -1,23 +1,243 @@
+use crate::utils::bitgrid::BitGrid;
 use crate::utils::c2::{C2Field, C2};
+use fxhash::FxHashMap;
+use std::fmt::{Display, Formatter};
 
-pub type Maze = (C2Field<bool>, C2, C2);
+pub type Maze = (BitGrid, C2, C2);
 
-pub fn parse_maze(input: &str) -> Maze {
-    let mut s = C2::new(-1, -1);
-    let mut e = C2::new(-1, -1);
+/// How many times a marker may appear in a maze.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[allow(dead_code)]
+pub enum Occurs {
+    Once,
+    AtMostOnce,
+    AtLeastOnce,
+    Any,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum MazeError {
+    UnknownCharacter { ch: char, at: C2 },
+    MissingMarker(String),
+    DuplicateMarker { name: String, first: C2, second: C2 },
+}
+
+impl Display for MazeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MazeError::UnknownCharacter { ch, at } => {
+                write!(f, "Unknown character '{}' at {},{}", ch, at.x, at.y)
+            }
+            MazeError::MissingMarker(name) => write!(f, "Missing marker '{}'", name),
+            MazeError::DuplicateMarker {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "Marker '{}' at {},{} and again at {},{}",
+                name, first.x, first.y, second.x, second.y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {}
+
+#[derive(Clone, Debug)]
+struct MarkerSpec {
+    name: String,
+    ch: char,
+    occurs: Occurs,
+}
+
+/// Describes which characters of a maze are open, walls or named markers.
+/// Markers are open cells whose positions are reported by name.
+#[derive(Clone, Debug)]
+pub struct MazeSpec {
+    open: Vec<char>,
+    walls: Vec<char>,
+    markers: Vec<MarkerSpec>,
+    other_is_wall: bool,
+}
+
+impl Default for MazeSpec {
+    fn default() -> Self {
+        Self {
+            open: vec!['.'],
+            walls: vec!['#'],
+            markers: Vec::new(),
+            other_is_wall: false,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl MazeSpec {
+    /// `.` open, `#` wall, no markers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Single required `S` start and `E` end.
+    pub fn start_end() -> Self {
+        Self::new()
+            .marker("start", 'S', Occurs::Once)
+            .marker("end", 'E', Occurs::Once)
+    }
+
+    pub fn open(mut self, ch: char) -> Self {
+        self.assert_unused(ch);
+        self.open.push(ch);
+        self
+    }
+
+    pub fn wall(mut self, ch: char) -> Self {
+        self.assert_unused(ch);
+        self.walls.push(ch);
+        self
+    }
 
-    let map = C2Field::from_string_indexed(input, &mut |c, ch| match ch {
-        'S' => {
-            s = c;
-            true
+    pub fn marker(mut self, name: &str, ch: char, occurs: Occurs) -> Self {
+        self.assert_unused(ch);
+        self.markers.push(MarkerSpec {
+            name: name.to_string(),
+            ch,
+            occurs,
+        });
+        self
+    }
+
+    /// Treat characters that are not described as walls instead of failing.
+    pub fn other_is_wall(mut self) -> Self {
+        self.other_is_wall = true;
+        self
+    }
+
+    fn assert_unused(&self, ch: char) {
+        assert!(
+            !self.open.contains(&ch)
+                && !self.walls.contains(&ch)
+                && self.markers.iter().all(|m| m.ch != ch),
+            "'{ch}' is already used"
+        );
+    }
+
+    pub fn parse(&self, input: &str) -> Result<ParsedMaze, MazeError> {
+        let mut found: Vec<Vec<C2>> = vec![Vec::new(); self.markers.len()];
+        let mut error = None;
+
+        let field = C2Field::from_string_indexed(input, &mut |c, ch| {
+            if let Some(i) = self.markers.iter().position(|m| m.ch == ch) {
+                found[i].push(c);
+                true
+            } else if self.open.contains(&ch) {
+                true
+            } else if self.walls.contains(&ch) || self.other_is_wall {
+                false
+            } else {
+                error.get_or_insert(MazeError::UnknownCharacter { ch, at: c });
+                false
+            }
+        });
+        if let Some(error) = error {
+            return Err(error);
         }
-        'E' => {
-            e = c;
-            true
+
+        let mut markers = FxHashMap::default();
+        for (spec, positions) in self.markers.iter().zip(found) {
+            let single = matches!(spec.occurs, Occurs::Once | Occurs::AtMostOnce);
+            let required = matches!(spec.occurs, Occurs::Once | Occurs::AtLeastOnce);
+            if required && positions.is_empty() {
+                return Err(MazeError::MissingMarker(spec.name.clone()));
+            }
+            if single && positions.len() > 1 {
+                return Err(MazeError::DuplicateMarker {
+                    name: spec.name.clone(),
+                    first: positions[0],
+                    second: positions[1],
+                });
+            }
+            markers.insert(spec.name.clone(), positions);
         }
-        '.' => true,
-        _ => false,
-    });
 
-    (map, s, e)
+        Ok(ParsedMaze {
+            field: BitGrid::from(&field),
+            markers,
+        })
+    }
+}
+
+/// Maze read with a [`MazeSpec`], set cells are open.
+pub struct ParsedMaze {
+    pub field: BitGrid,
+    markers: FxHashMap<String, Vec<C2>>,
+}
+
+#[allow(dead_code)]
+impl ParsedMaze {
+    /// First position of marker `name`, in reading order.
+    pub fn marker(&self, name: &str) -> Option<C2> {
+        self.markers(name).first().copied()
+    }
+
+    /// All positions of marker `name`, in reading order.
+    pub fn markers(&self, name: &str) -> &[C2] {
+        self.markers.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Field with the `start` and `end` markers of [`MazeSpec::start_end`].
+    pub fn into_start_end(self) -> Maze {
+        let start = self.marker("start").expect("Spec requires start");
+        let end = self.marker("end").expect("Spec requires end");
+        (self.field, start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = "#####\n#S.k#\n#.#D#\n#k.E#\n#####\n";
+
+    #[test]
+    fn named_markers() {
+        let maze = MazeSpec::start_end()
+            .marker("key", 'k', Occurs::Any)
+            .marker("door", 'D', Occurs::AtMostOnce)
+            .marker("portal", 'P', Occurs::AtMostOnce)
+            .parse(MAZE)
+            .unwrap();
+        assert_eq!(maze.marker("start"), Some(C2::new(1, 1)));
+        assert_eq!(maze.markers("key"), &[C2::new(3, 1), C2::new(1, 3)]);
+        assert_eq!(maze.marker("portal"), None);
+        assert!(maze.field.get(&C2::new(3, 2)));
+        assert!(!maze.field.get(&C2::new(2, 2)));
+        let (_, start, end) = maze.into_start_end();
+        assert_eq!((start, end), (C2::new(1, 1), C2::new(3, 3)));
+    }
+
+    #[test]
+    fn errors() {
+        let err = MazeSpec::start_end().parse(MAZE).err();
+        assert_eq!(
+            err,
+            Some(MazeError::UnknownCharacter {
+                ch: 'k',
+                at: C2::new(3, 1)
+            })
+        );
+        assert!(MazeSpec::start_end().other_is_wall().parse(MAZE).is_ok());
+
+        let err = MazeSpec::start_end().parse("#S#\n#.#\n").err();
+        assert_eq!(err, Some(MazeError::MissingMarker("end".to_string())));
+
+        let err = MazeSpec::start_end()
+            .other_is_wall()
+            .marker("key", 'k', Occurs::AtMostOnce)
+            .parse(MAZE)
+            .err()
+            .map(|e| e.to_string());
+        assert_eq!(err.as_deref(), Some("Marker 'key' at 3,1 and again at 1,3"));
+    }
 }
@@ -0,0 +1,325 @@
+use fxhash::{FxBuildHasher, FxHashMap};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Lookup counters of a memo table.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct MemoStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[allow(dead_code)]
+impl MemoStats {
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups() == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.lookups() as f64
+        }
+    }
+}
+
+/// Memo table owned by a single computation, dropped together with it.
+///
+/// Keys may borrow from the input (`Memo<&str, _>`), so no owned copies are needed.
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    store: FxHashMap<K, V>,
+    stats: MemoStats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            store: FxHashMap::default(),
+            stats: MemoStats::default(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K, V> Memo<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self.store.get(key).cloned();
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> V {
+        self.store.insert(key, value.clone());
+        value
+    }
+
+    /// Cached value for `key`, or the result of `f`, which may recurse through the table.
+    pub fn compute<F>(&mut self, key: K, f: F) -> V
+    where
+        F: FnOnce(&mut Self, &K) -> V,
+    {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = f(self, &key);
+        self.insert(key, value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    pub fn clear(&mut self) {
+        self.store.clear();
+        self.stats = MemoStats::default();
+    }
+}
+
+/// Memo table for keys in `0..capacity`, backed by a `Vec` instead of hashing.
+#[derive(Clone, Debug)]
+pub struct DenseMemo<V> {
+    store: Vec<Option<V>>,
+    stats: MemoStats,
+}
+
+#[allow(dead_code)]
+impl<V> DenseMemo<V>
+where
+    V: Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            store: vec![None; capacity],
+            stats: MemoStats::default(),
+        }
+    }
+
+    pub fn get(&mut self, key: usize) -> Option<V> {
+        let value = self.store[key].clone();
+        if value.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: usize, value: V) -> V {
+        self.store[key] = Some(value.clone());
+        value
+    }
+
+    /// Cached value for `key`, or the result of `f`, which may recurse through the table.
+    pub fn compute<F>(&mut self, key: usize, f: F) -> V
+    where
+        F: FnOnce(&mut Self, usize) -> V,
+    {
+        if let Some(value) = self.get(key) {
+            return value;
+        }
+        let value = f(self, key);
+        self.insert(key, value)
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    pub fn clear(&mut self) {
+        self.store.fill(None);
+        self.stats = MemoStats::default();
+    }
+}
+
+const SHARDS: usize = 16;
+
+/// Thread-safe memo table for sharing between rayon workers, split into locked shards.
+///
+/// Locks are never held while a value is computed, so two workers may compute the same key.
+pub struct SharedMemo<K, V> {
+    shards: Vec<Mutex<FxHashMap<K, V>>>,
+    hasher: FxBuildHasher,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K, V> Default for SharedMemo<K, V> {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARDS)
+                .map(|_| Mutex::new(FxHashMap::default()))
+                .collect(),
+            hasher: FxBuildHasher::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K, V> SharedMemo<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn shard<Q>(&self, key: &Q) -> &Mutex<FxHashMap<K, V>>
+    where
+        Q: Hash + ?Sized,
+    {
+        &self.shards[self.hasher.hash_one(key) as usize % SHARDS]
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self
+            .shard(key)
+            .lock()
+            .expect("Poisoned memo")
+            .get(key)
+            .cloned();
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    pub fn insert(&self, key: K, value: V) -> V {
+        self.shard(&key)
+            .lock()
+            .expect("Poisoned memo")
+            .insert(key, value.clone());
+        value
+    }
+
+    /// Cached value for `key`, or the result of `f`, which may recurse through the table.
+    pub fn compute<F>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = f(&key);
+        self.insert(key, value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| s.lock().expect("Poisoned memo").len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.compute(n, |memo, n| fib(n - 1, memo) + fib(n - 2, memo))
+    }
+
+    #[test]
+    fn memo_recursion_and_stats() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(90, &mut memo), 2880067194370816120);
+        assert_eq!(memo.len(), 89);
+        assert_eq!(memo.stats().misses, 89);
+        assert_eq!(memo.stats().hits, 87);
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(memo.stats(), MemoStats::default());
+    }
+
+    #[test]
+    fn borrowed_keys() {
+        let owned = String::from("abcabc");
+        let mut memo: Memo<&str, usize> = Memo::new();
+        memo.insert(&owned[3..], 1);
+        assert_eq!(memo.get("abc"), Some(1));
+        assert_eq!(memo.get(&owned[..3]), Some(1));
+        assert_eq!(memo.get("ab"), None);
+    }
+
+    #[test]
+    fn dense_memo() {
+        let mut memo = DenseMemo::new(10);
+        assert_eq!(memo.compute(3, |_, k| k * 2), 6);
+        assert_eq!(memo.compute(3, |_, _| 0), 6);
+        assert_eq!(memo.stats(), MemoStats { hits: 1, misses: 1 });
+    }
+
+    fn collatz(n: u64, memo: &SharedMemo<u64, u32>) -> u32 {
+        if n == 1 {
+            return 0;
+        }
+        memo.compute(n, |n| {
+            1 + collatz(if n % 2 == 0 { n / 2 } else { 3 * n + 1 }, memo)
+        })
+    }
+
+    #[test]
+    fn shared_memo_across_threads() {
+        let memo = SharedMemo::new();
+        let longest = (1..10_000u64)
+            .into_par_iter()
+            .map(|n| collatz(n, &memo))
+            .max();
+        assert_eq!(longest, Some(261));
+        assert_eq!(memo.get(&27), Some(111));
+        assert!(memo.stats().hits > 0);
+    }
+}
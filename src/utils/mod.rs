@@ -1,17 +1,10 @@
+pub mod bitgrid;
 pub mod c2;
+pub mod cn;
+pub mod cycle;
+pub mod interval;
 pub mod maze;
-
-pub fn binary_find<F>(mut low: usize, mut high: usize, f: F) -> usize
-where
-    F: Fn(usize) -> bool,
-{
-    while low < high {
-        let mid = low + (high - low) / 2;
-        if f(mid) {
-            low = mid + 1;
-        } else {
-            high = mid;
-        }
-    }
-    low
-}
+pub mod memo;
+pub mod num;
+pub mod parse;
+pub mod search;
@@ -0,0 +1,173 @@
+/// Greatest common divisor, non-negative except for `gcd(i64::MIN, 0)` and
+/// `gcd(i64::MIN, i64::MIN)`, where 2^63 doesn't fit and wraps to `i64::MIN`.
+pub fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a as i64
+}
+
+/// Least common multiple, always non-negative, `None` when it does not fit into `i64`.
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b).and_then(i64::checked_abs)
+}
+
+/// Returns `(g, x, y)` where `g = gcd(a, b)` and `a * x + b * y = g`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a as i128, b as i128);
+    let (mut old_x, mut x) = (1i128, 0i128);
+    let (mut old_y, mut y) = (0i128, 1i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (old_r, old_x, old_y) = (-old_r, -old_x, -old_y);
+    }
+    (old_r as i64, old_x as i64, old_y as i64)
+}
+
+/// `x` in `0..m` with `a * x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+#[allow(dead_code)]
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a, m);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(m.abs()))
+}
+
+/// Chinese remainder theorem over `(remainder, modulus)` pairs, moduli need not be coprime.
+///
+/// Returns `(x, m)` where `x` in `0..m` satisfies every congruence and `m` is the lcm of all
+/// moduli, or `None` when the congruences contradict each other or `m` overflows.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut m: i128 = 1;
+    for &(r, n) in congruences {
+        let n = n.abs() as i128;
+        if n == 0 {
+            return None;
+        }
+        let r = (r as i128).rem_euclid(n);
+        let (g, p, _) = extended_gcd(m as i64, n as i64);
+        let g = g as i128;
+        if (r - x) % g != 0 {
+            return None;
+        }
+        let step = n / g;
+        let k = ((r - x) / g % step * (p as i128 % step)).rem_euclid(step);
+        x += m * k;
+        m *= step;
+        if m > i64::MAX as i128 {
+            return None;
+        }
+        x = x.rem_euclid(m);
+    }
+    Some((x as i64, m as i64))
+}
+
+/// Integer solutions of a 2x2 linear system.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LinearSolution {
+    Unique(i128, i128),
+    None,
+    /// The equations are dependent and have infinitely many integer solutions.
+    Infinite,
+}
+
+/// Solves `a[0][0] * x + a[0][1] * y = b[0]` and `a[1][0] * x + a[1][1] * y = b[1]` over
+/// integers, intermediate products are computed in `i128`.
+pub fn solve_2x2(a: [[i64; 2]; 2], b: [i64; 2]) -> LinearSolution {
+    let [[a00, a01], [a10, a11]] = a.map(|r| r.map(|v| v as i128));
+    let [b0, b1] = b.map(|v| v as i128);
+
+    let det = a00 * a11 - a01 * a10;
+    if det != 0 {
+        let dx = b0 * a11 - a01 * b1;
+        let dy = a00 * b1 - b0 * a10;
+        return if dx % det == 0 && dy % det == 0 {
+            LinearSolution::Unique(dx / det, dy / det)
+        } else {
+            LinearSolution::None
+        };
+    }
+
+    // Rows are parallel: the system is consistent only if the augmented rows are as well.
+    if a00 * b1 - a10 * b0 != 0 || a01 * b1 - a11 * b0 != 0 {
+        return LinearSolution::None;
+    }
+    let (c0, c1, rhs) = if a00 != 0 || a01 != 0 {
+        (a00, a01, b0)
+    } else {
+        (a10, a11, b1)
+    };
+    if c0 == 0 && c1 == 0 {
+        return if rhs == 0 {
+            LinearSolution::Infinite
+        } else {
+            LinearSolution::None
+        };
+    }
+    if rhs % gcd(c0 as i64, c1 as i64) as i128 == 0 {
+        LinearSolution::Infinite
+    } else {
+        LinearSolution::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_lcm() {
+        assert_eq!(gcd(12, -18), 6);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(i64::MAX, i64::MAX - 1), None);
+        assert_eq!(gcd(i64::MIN, 0), i64::MIN);
+        assert_eq!(gcd(i64::MIN, i64::MIN), i64::MIN);
+        assert_eq!(gcd(i64::MIN, 6), 2);
+        assert_eq!(lcm(i64::MIN, 1), None);
+        assert_eq!(lcm(i64::MIN, i64::MIN), None);
+        assert_eq!(lcm(i64::MIN / 2, 2), Some(1 << 62));
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(-1, 101), (5, 103)]), Some((10099, 10403)));
+    }
+
+    #[test]
+    fn linear_2x2() {
+        assert_eq!(
+            solve_2x2([[94, 22], [34, 67]], [8400, 5400]),
+            LinearSolution::Unique(80, 40)
+        );
+        assert_eq!(
+            solve_2x2([[26, 67], [66, 21]], [12748, 12176]),
+            LinearSolution::None
+        );
+        assert_eq!(
+            solve_2x2([[2, 4], [3, 6]], [10, 15]),
+            LinearSolution::Infinite
+        );
+        assert_eq!(solve_2x2([[2, 4], [3, 6]], [10, 16]), LinearSolution::None);
+        assert_eq!(solve_2x2([[2, 4], [3, 6]], [3, 4]), LinearSolution::None);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::utils::c2::{C2Field, C2};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Parse failure with a 1-based position in the input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Moves an error reported for a single line (line 1) to `line` of the whole input.
+    pub fn on_line(self, line: usize) -> Self {
+        Self {
+            line: self.line + line - 1,
+            ..self
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// 1-based column where `part`, a slice of `line`, starts.
+pub fn column_of(line: &str, part: &str) -> usize {
+    let offset = part.as_ptr() as usize - line.as_ptr() as usize;
+    line[..offset].chars().count() + 1
+}
+
+fn parse_value<T: FromStr>(line: &str, part: &str) -> ParseResult<T> {
+    part.trim().parse().map_err(|_| {
+        ParseError::new(
+            1,
+            column_of(line, part),
+            format!("Can't parse '{}' as {}", part, std::any::type_name::<T>()),
+        )
+    })
+}
+
+/// Every integer in `line`, a `-` directly in front of digits is treated as a sign.
+pub fn integers<T: FromStr>(line: &str) -> ParseResult<Vec<T>> {
+    integers_until(line).map(|(values, _)| values)
+}
+
+/// [`integers`] and the byte offset just past the last one, 0 if there is none.
+fn integers_until<T: FromStr>(line: &str) -> ParseResult<(Vec<T>, usize)> {
+    let bytes = line.as_bytes();
+    let mut values = Vec::new();
+    let mut end = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let signed = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+            && (i == 0 || !bytes[i - 1].is_ascii_alphanumeric());
+        if signed || bytes[i].is_ascii_digit() {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            values.push(parse_value(line, &line[start..i])?);
+            end = i;
+        } else {
+            i += 1;
+        }
+    }
+    Ok((values, end))
+}
+
+/// Exactly `N` integers from `line`, a wrong count is reported after the last number found.
+pub fn integers_n<T: FromStr, const N: usize>(line: &str) -> ParseResult<[T; N]> {
+    let (values, end) = integers_until(line)?;
+    let found = values.len();
+    values.try_into().map_err(|_| {
+        ParseError::new(
+            1,
+            column_of(line, &line[end..]),
+            format!("Expected {} numbers, found {}", N, found),
+        )
+    })
+}
+
+fn missing_separator(line: &str, separator: &str) -> ParseError {
+    ParseError::new(
+        1,
+        line.chars().count() + 1,
+        format!("Missing '{}'", separator),
+    )
+}
+
+/// Blank-line separated blocks together with the 1-based line number they start at.
+pub fn blocks(input: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let blank = line.trim().is_empty();
+        match (start, blank) {
+            (None, false) => start = Some((i + 1, offset)),
+            (Some((number, from)), true) => {
+                result.push((number, input[from..offset].trim_end()));
+                start = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some((number, from)) = start {
+        result.push((number, input[from..].trim_end()));
+    }
+    result
+}
+
+/// Splits `key<separator>value` and trims both sides.
+pub fn key_value<'a>(line: &'a str, separator: &str) -> ParseResult<(&'a str, &'a str)> {
+    line.split_once(separator)
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .ok_or_else(|| missing_separator(line, separator))
+}
+
+/// Parses `a<separator>b`, for example `3,4`.
+pub fn pair<T: FromStr>(line: &str, separator: &str) -> ParseResult<(T, T)> {
+    let (a, b) = line
+        .split_once(separator)
+        .ok_or_else(|| missing_separator(line, separator))?;
+    Ok((parse_value(line, a)?, parse_value(line, b)?))
+}
+
+/// Applies `f` to every non-empty line, errors get the line number of the whole input.
+pub fn lines<T, F>(input: &str, mut f: F) -> ParseResult<Vec<T>>
+where
+    F: FnMut(&str) -> ParseResult<T>,
+{
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| f(line).map_err(|e| e.on_line(i + 1)))
+        .collect()
+}
+
+/// Rectangular character grid, `mapping` returns `None` for characters it doesn't accept.
+pub fn grid<T, F>(input: &str, mut mapping: F) -> ParseResult<C2Field<T>>
+where
+    T: Clone + Default + PartialEq + Eq,
+    F: FnMut(C2, char) -> Option<T>,
+{
+    let mut width = None;
+    let mut height = 0;
+    let mut values = Vec::with_capacity(input.len());
+    for (number, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let len = line.chars().count();
+        let expected = *width.get_or_insert(len);
+        if expected != len {
+            return Err(ParseError::new(
+                number + 1,
+                len.min(expected) + 1,
+                format!("Line is {} long, expected {}", len, expected),
+            ));
+        }
+        for (x, ch) in line.chars().enumerate() {
+            let value = mapping(C2::new(x as i32, height as i32), ch).ok_or_else(|| {
+                ParseError::new(number + 1, x + 1, format!("Unexpected character '{}'", ch))
+            })?;
+            values.push(value);
+        }
+        height += 1;
+    }
+
+    Ok(C2Field::from_vec(width.unwrap_or(0), height, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_integers() {
+        assert_eq!(integers::<i32>("p=0,4 v=3,-3").unwrap(), vec![0, 4, 3, -3]);
+        assert_eq!(integers::<u64>("Register A: 729").unwrap(), vec![729]);
+        assert_eq!(integers::<i32>("x-1 to -2").unwrap(), vec![1, -2]);
+        let [a, b]: [i32; 2] = integers_n("3   4").unwrap();
+        assert_eq!((a, b), (3, 4));
+        assert_eq!(
+            integers::<u8>("1,300").unwrap_err(),
+            ParseError::new(1, 3, "Can't parse '300' as u8")
+        );
+        assert_eq!(
+            integers_n::<i32, 3>("1 2 x").unwrap_err(),
+            ParseError::new(1, 4, "Expected 3 numbers, found 2")
+        );
+        assert_eq!(integers_n::<i32, 1>("none").unwrap_err().column, 1);
+    }
+
+    #[test]
+    fn splits_blocks() {
+        let input = "a\nb\n\n\nc\n\nd";
+        assert_eq!(blocks(input), vec![(1, "a\nb"), (5, "c"), (7, "d")]);
+    }
+
+    #[test]
+    fn pairs_and_lines() {
+        assert_eq!(key_value("x00: 1", ":").unwrap(), ("x00", "1"));
+        assert_eq!(pair::<i32>("5,4", ",").unwrap(), (5, 4));
+        let err = lines("1,2\n\n3;4", |l| pair::<i32>(l, ",")).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 4));
+        assert_eq!(key_value("x00 1", ":").unwrap_err().column, 6);
+        let err = lines("1,2\n3,x", |l| pair::<i32>(l, ",")).unwrap_err();
+        assert_eq!(err.to_string(), "2:3: Can't parse 'x' as i32");
+    }
+
+    #[test]
+    fn reads_grids() {
+        let field = grid("#.\n.#\n", |_, c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap();
+        assert!(field[C2::new(1, 1)]);
+        let Err(err) = grid("#.\n.x\n", |_, c| (c != 'x').then_some(c)) else {
+            panic!("Expected an error");
+        };
+        assert_eq!((err.line, err.column), (2, 2));
+        let Err(err) = grid("#.\n.\n", |_, c| Some(c)) else {
+            panic!("Expected an error");
+        };
+        assert_eq!(err.line, 2);
+
+        let mut seen = Vec::new();
+        let field = grid("\r\n#.\r\n\r\n.#", |at, c| {
+            seen.push(at);
+            Some(c)
+        })
+        .unwrap();
+        assert_eq!(field[C2::new(1, 1)], '#');
+        assert_eq!(seen.last(), Some(&C2::new(1, 1)));
+        assert_eq!(grid("#.#", |_, c| Some(c)).unwrap()[C2::new(2, 0)], '#');
+    }
+}
@@ -0,0 +1,184 @@
+use rayon::prelude::*;
+
+/// Integers that can be searched over. Distances are measured as `u128`, so any range of any
+/// implementing type fits, including the full range of `i128`.
+pub trait SearchInt: Copy + Ord + Send + Sync {
+    /// `high - low` for `low <= high`.
+    fn distance(low: Self, high: Self) -> u128;
+    /// `self + d`, the caller guarantees the result fits.
+    fn offset(self, d: u128) -> Self;
+    fn checked_offset(self, d: u128) -> Option<Self>;
+}
+
+macro_rules! search_int {
+    ($($t:ty => $u:ty, $checked:ident;)*) => {$(
+        impl SearchInt for $t {
+            #[inline]
+            fn distance(low: Self, high: Self) -> u128 {
+                high.wrapping_sub(low) as $u as u128
+            }
+
+            #[inline]
+            fn offset(self, d: u128) -> Self {
+                self.wrapping_add(d as $u as $t)
+            }
+
+            #[inline]
+            fn checked_offset(self, d: u128) -> Option<Self> {
+                <$u>::try_from(d).ok().and_then(|d| self.$checked(d))
+            }
+        }
+    )*};
+}
+
+search_int! {
+    u32 => u32, checked_add;
+    u64 => u64, checked_add;
+    u128 => u128, checked_add;
+    usize => usize, checked_add;
+    i32 => u32, checked_add_unsigned;
+    i64 => u64, checked_add_unsigned;
+    i128 => u128, checked_add_unsigned;
+}
+
+/// First value in `low..high` where `predicate` holds, for predicates that are `false` up to
+/// some point and `true` after it.
+pub fn first_true<T, F>(mut low: T, mut high: T, predicate: F) -> Option<T>
+where
+    T: SearchInt,
+    F: Fn(T) -> bool,
+{
+    let end = high;
+    while low < high {
+        let mid = low.offset(T::distance(low, high) / 2);
+        if predicate(mid) {
+            high = mid;
+        } else {
+            low = mid.offset(1);
+        }
+    }
+    (low < end).then_some(low)
+}
+
+/// Last value in `low..high` where `predicate` holds, for predicates that are `true` up to
+/// some point and `false` after it.
+#[allow(dead_code)]
+pub fn last_true<T, F>(low: T, high: T, predicate: F) -> Option<T>
+where
+    T: SearchInt,
+    F: Fn(T) -> bool,
+{
+    if low >= high || !predicate(low) {
+        return None;
+    }
+    let first_false = first_true(low, high, |x| !predicate(x)).unwrap_or(high);
+    // first_false > low, because predicate(low) holds
+    Some(low.offset(T::distance(low, first_false) - 1))
+}
+
+/// [`first_true`] without an upper limit: probes `start + 1, 3, 7, 15, ...` until the
+/// predicate holds, then searches inside the last step. `None` if the type runs out first.
+#[allow(dead_code)]
+pub fn gallop_first_true<T, F>(start: T, predicate: F) -> Option<T>
+where
+    T: SearchInt,
+    F: Fn(T) -> bool,
+{
+    if predicate(start) {
+        return Some(start);
+    }
+    let mut low = start;
+    let mut step = 1u128;
+    loop {
+        let probe = start.checked_offset(step)?;
+        if predicate(probe) {
+            return first_true(low.offset(1), probe, &predicate).or(Some(probe));
+        }
+        low = probe;
+        step = step.checked_mul(2)?.checked_add(1)?;
+    }
+}
+
+/// [`first_true`] that evaluates `ways - 1` split points of the range in parallel per round,
+/// for predicates that are expensive enough to be worth a thread each.
+pub fn par_first_true<T, F>(mut low: T, mut high: T, ways: usize, predicate: F) -> Option<T>
+where
+    T: SearchInt,
+    F: Fn(T) -> bool + Sync,
+{
+    assert!(ways >= 2, "Needs at least two ways to split");
+    let end = high;
+    while low < high {
+        let (distance, k) = (T::distance(low, high), ways as u128);
+        // low + distance * i / k, without overflowing u128
+        let points: Vec<T> = (1..k)
+            .map(|i| low.offset(distance / k * i + distance % k * i / k))
+            .collect();
+        let results: Vec<bool> = points.par_iter().map(|&p| predicate(p)).collect();
+        match results.iter().position(|&r| r) {
+            Some(i) => {
+                high = points[i];
+                if i > 0 {
+                    low = points[i - 1].offset(1);
+                }
+            }
+            None => low = points[ways - 2].offset(1),
+        }
+    }
+    (low < end).then_some(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_and_last() {
+        assert_eq!(first_true(0usize, 100, |x| x >= 37), Some(37));
+        assert_eq!(first_true(0usize, 100, |x| x >= 100), None);
+        assert_eq!(first_true(5u32, 5, |_| true), None);
+        assert_eq!(first_true(-50i64, 50, |x| x * x * x > -1000), Some(-9));
+        assert_eq!(last_true(0u64, 100, |x| x * x <= 50), Some(7));
+        assert_eq!(last_true(0u64, 100, |_| true), Some(99));
+        assert_eq!(last_true(0u64, 100, |_| false), None);
+    }
+
+    #[test]
+    fn extreme_ranges() {
+        assert_eq!(first_true(i128::MIN, i128::MAX, |x| x >= 0), Some(0));
+        assert_eq!(
+            first_true(0u128, u128::MAX, |x| x > u128::MAX - 3),
+            Some(u128::MAX - 2)
+        );
+        assert_eq!(
+            first_true(i32::MIN, i32::MAX, |x| x > i32::MIN),
+            Some(i32::MIN + 1)
+        );
+    }
+
+    #[test]
+    fn galloping() {
+        assert_eq!(
+            gallop_first_true(0u64, |x| x >= 1_000_000_007),
+            Some(1_000_000_007)
+        );
+        assert_eq!(gallop_first_true(10i64, |x| x >= 10), Some(10));
+        assert_eq!(gallop_first_true(10i64, |x| x >= 12), Some(12));
+        assert_eq!(gallop_first_true(-100i32, |x| x >= -3), Some(-3));
+        assert_eq!(gallop_first_true(0u32, |_| false), None);
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        for target in 0..40u64 {
+            for ways in 2..6 {
+                assert_eq!(
+                    par_first_true(3u64, 35, ways, |x| x >= target),
+                    first_true(3u64, 35, |x| x >= target),
+                    "target {target}, ways {ways}"
+                );
+            }
+        }
+        assert_eq!(par_first_true(i64::MIN, i64::MAX, 8, |x| x >= 42), Some(42));
+    }
+}